@@ -0,0 +1,87 @@
+//! Queries on a [`SpatialIndex`] of 5000 units spread over a 4000 x 4000 map.
+//! Run with `cargo bench --bench spatial_index`.
+
+use bevy::prelude::*;
+use bevy_game::spatial_index::SpatialIndex;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ENTITIES: u32 = 5000;
+const MAP_SIZE: f32 = 4000.;
+const ITERATIONS: u32 = 1000;
+
+fn bench(name: &str, mut run: impl FnMut(u32)) {
+    // Warm up caches and the allocator
+    for iteration in 0..ITERATIONS / 10 {
+        run(iteration);
+    }
+    let start = Instant::now();
+    for iteration in 0..ITERATIONS {
+        run(iteration);
+    }
+    let per_iteration = start.elapsed() / ITERATIONS;
+    println!("{name:<32} {per_iteration:>12?}");
+}
+
+fn random_point(rng: &mut StdRng) -> Vec2 {
+    Vec2::new(
+        rng.gen_range(-MAP_SIZE / 2.0..MAP_SIZE / 2.0),
+        rng.gen_range(-MAP_SIZE / 2.0..MAP_SIZE / 2.0),
+    )
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(5000);
+    let entities: Vec<(Entity, Vec2)> = (0..ENTITIES)
+        .map(|index| (Entity::from_raw(index), random_point(&mut rng)))
+        .collect();
+    let queries: Vec<Vec2> = (0..ITERATIONS).map(|_| random_point(&mut rng)).collect();
+    let mut index = SpatialIndex::default();
+
+    bench("insert 5000", |_| {
+        index = SpatialIndex::default();
+        for (entity, position) in entities.iter() {
+            index.insert(*entity, *position);
+        }
+    });
+    let mut moved = entities.clone();
+    bench("move 5000", |iteration| {
+        // Everyone takes a small step, as units do every frame
+        let step = Vec2::from_angle(iteration as f32) * 3.;
+        for (entity, position) in moved.iter_mut() {
+            *position += step;
+            index.insert(*entity, *position);
+        }
+    });
+    bench("radius 120", |iteration| {
+        let center = queries[iteration as usize];
+        black_box(index.in_radius(center, 120.).count());
+    });
+    bench("radius 120 for all 5000", |_| {
+        let total: usize = moved
+            .iter()
+            .map(|(_, position)| index.in_radius(*position, 120.).count())
+            .sum();
+        black_box(total);
+    });
+    bench("rect 800 x 600", |iteration| {
+        let center = queries[iteration as usize];
+        let rect = Rect::from_center_size(center, Vec2::new(800., 600.));
+        black_box(index.in_rect(rect).count());
+    });
+    bench("nearest 8", |iteration| {
+        black_box(index.nearest(queries[iteration as usize], 8));
+    });
+
+    // For comparison, a radius query going through every entity
+    bench("radius 120, brute force", |iteration| {
+        let center = queries[iteration as usize];
+        black_box(
+            moved
+                .iter()
+                .filter(|(_, position)| position.distance_squared(center) <= 120. * 120.)
+                .count(),
+        );
+    });
+}
@@ -1 +1,3 @@
+#![allow(clippy::type_complexity)]
+
 pub mod ron_generation;
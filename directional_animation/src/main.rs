@@ -1,3 +1,162 @@
-pub mod ron_generation;
+//! Animation viewer, previews any `.anim.ron` without the game.
+//!
+//! `cargo run -p directional_animation -- assets/wolf.anim.ron [assets folder]`
+//!
+//! `cargo run -p directional_animation -- validate assets/wolf.anim.ron [assets folder]`
+//! checks the collection instead and exits with an error if anything is wrong.
+//!
+//! `cargo run -p directional_animation -- migrate assets/wolf.anim.ron [assets folder]`
+//! rewrites a file of an older format version in the current one.
+//!
+//! Only `.anim.ron` files can be opened, `.anim.bin` manifests store names as the game's
+//! variant indices.
+//!
+//! Frame paths are resolved relative to the assets folder, which defaults to the folder of the file.
+//! Rotations are read as degrees, 0 facing down the screen and growing counter-clockwise like the
+//! game's files. `--zero <0 | 90 | 180 | 270>` sets the screen angle of 0, counter-clockwise from
+//! the right, and `--clockwise` makes angles grow clockwise.
 
-fn main() {}
+use bevy::prelude::*;
+use directional_animation::ron_generation::{
+    dynamic::DynamicAnimationTypes,
+    format::ANIMATIONS_FORMAT_VERSION,
+    plugin::{AnimatePlugin, LoadAnimationPlugin},
+    validate::validate_file,
+    viewer::AnimationViewerPlugin,
+    AnimationLoader, AnimationTypes,
+};
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "usage: directional_animation [validate | migrate] <file.anim.ron> \
+                     [assets folder] [--zero <0 | 90 | 180 | 270>] [--clockwise]";
+
+fn main() {
+    let mut zero = 270;
+    let mut clockwise = false;
+    let mut positional = Vec::new();
+    let mut all_args = std::env::args().skip(1);
+    while let Some(arg) = all_args.next() {
+        match arg.as_str() {
+            "--clockwise" => clockwise = true,
+            "--zero" => match all_args.next().and_then(|degrees| degrees.parse().ok()) {
+                Some(degrees @ (0 | 90 | 180 | 270)) => zero = degrees,
+                _ => {
+                    eprintln!("--zero expects 0, 90, 180 or 270\n{}", USAGE);
+                    std::process::exit(1);
+                }
+            },
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut args = positional.into_iter().peekable();
+    let command = args.next_if(|arg| arg == "validate" || arg == "migrate");
+    let Some(file) = args.next() else {
+        eprintln!("{}", USAGE);
+        std::process::exit(1);
+    };
+    let file = Path::new(&file);
+    if file.to_string_lossy().ends_with(".anim.bin") {
+        eprintln!(
+            "{}: binary manifests only store variant indices, open the .anim.ron instead",
+            file.display()
+        );
+        std::process::exit(1);
+    }
+    let assets = args
+        .next()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| file.parent().map(Path::to_path_buf).unwrap_or_default());
+    let assets = assets.canonicalize().unwrap_or(assets);
+    let file = file.canonicalize().unwrap_or(file.to_path_buf());
+    let Ok(collection) = file.strip_prefix(&assets) else {
+        eprintln!(
+            "{} is not inside the assets folder {}",
+            file.display(),
+            assets.display()
+        );
+        std::process::exit(1);
+    };
+
+    if command.as_deref() == Some("migrate") {
+        if let Err(e) = migrate(&file) {
+            eprintln!("{}: {}", collection.display(), e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if command.as_deref() == Some("validate") {
+        let report = match validate_file::<DynamicAnimationTypes>(&file, &assets) {
+            Ok(report) => report,
+            Err(e) => {
+                eprintln!("{}: {}", collection.display(), e);
+                std::process::exit(1);
+            }
+        };
+        for issue in report.issues.iter() {
+            println!("{}", issue);
+        }
+        println!(
+            "{}: {} clips, {} issues",
+            collection.display(),
+            report.clips,
+            report.issues.len()
+        );
+        std::process::exit(if report.is_ok() { 0 } else { 1 });
+    }
+
+    let collection = collection.to_string_lossy().to_string();
+    // The convention is part of the type, one for every zero angle and winding
+    match (zero, clockwise) {
+        (0, false) => view::<DynamicAnimationTypes<0, false>>(&assets, collection),
+        (0, true) => view::<DynamicAnimationTypes<0, true>>(&assets, collection),
+        (90, false) => view::<DynamicAnimationTypes<90, false>>(&assets, collection),
+        (90, true) => view::<DynamicAnimationTypes<90, true>>(&assets, collection),
+        (180, false) => view::<DynamicAnimationTypes<180, false>>(&assets, collection),
+        (180, true) => view::<DynamicAnimationTypes<180, true>>(&assets, collection),
+        (270, true) => view::<DynamicAnimationTypes<270, true>>(&assets, collection),
+        _ => view::<DynamicAnimationTypes>(&assets, collection),
+    }
+}
+
+fn view<T: AnimationTypes>(assets: &Path, collection: String) {
+    App::new()
+        .add_plugins(
+            DefaultPlugins
+                .set(AssetPlugin {
+                    file_path: assets.to_string_lossy().to_string(),
+                    ..default()
+                })
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: format!("Animation viewer - {}", collection),
+                        ..default()
+                    }),
+                    ..default()
+                }),
+        )
+        .add_plugins((
+            LoadAnimationPlugin::<T>::new(vec![collection]),
+            AnimatePlugin::<T>::default(),
+            AnimationViewerPlugin::<T>::default(),
+        ))
+        .add_systems(Startup, |mut commands: Commands| {
+            commands.spawn(Camera2dBundle::default());
+        })
+        .run();
+}
+
+fn migrate(file: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let collection = AnimationLoader::<DynamicAnimationTypes>::parse(&std::fs::read(file)?)?;
+    if collection.header.version == ANIMATIONS_FORMAT_VERSION {
+        println!("already at version {}", ANIMATIONS_FORMAT_VERSION);
+        return Ok(());
+    }
+    let ron_string = ron::ser::to_string_pretty(&collection, ron::ser::PrettyConfig::default())?;
+    std::fs::write(file, ron_string)?;
+    println!(
+        "migrated from version {} to {}",
+        collection.header.version, ANIMATIONS_FORMAT_VERSION
+    );
+    Ok(())
+}
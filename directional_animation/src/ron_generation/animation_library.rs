@@ -1,6 +1,19 @@
-use bevy::{asset::LoadState, prelude::*, render::texture::ImageSampler, utils::HashMap};
+use bevy::{
+    asset::LoadState,
+    prelude::*,
+    sprite::{Anchor, TextureAtlasBuilderError},
+    utils::HashMap,
+};
 
-use super::{AnimationLoadData, AnimationTypes, AnimationsCollection, DirectionalRotationMatcher};
+use super::{
+    atlas::{generate_mips, AnimationAtlasSettings, AtlasSettings},
+    ron_label,
+    team_color::{key_color_mask, TeamColorMask},
+    trim::{trim_frames, TrimStats, TrimmedFrame},
+    AnimationLoadData, AnimationTypes, AnimationsCollection, DirectionalRotationMatcher,
+    SpriteMetadata,
+};
+use thiserror::Error;
 
 type ImageHandles = Vec<Handle<Image>>;
 
@@ -12,7 +25,10 @@ pub struct AnimationWithHandles<T: AnimationTypes> {
     pub animation: T::AnimationName,
     pub rotation: T::Rotation,
     pub frames: ImageHandles,
+    /// Team colour mask frames, empty unless the clip uses [`TeamColorMask::MaskFrames`].
+    pub masks: ImageHandles,
     pub fps: f32,
+    pub metadata: SpriteMetadata,
 }
 #[derive(PartialEq, Eq, Hash)]
 pub struct AnimationKey<T: AnimationTypes> {
@@ -21,12 +37,103 @@ pub struct AnimationKey<T: AnimationTypes> {
     pub rotation: T::Rotation,
 }
 
-#[derive(Component, Clone)]
+#[derive(Clone, Debug, Reflect)]
+pub struct AtlasPage {
+    pub texture_atlas_layout_handle: Handle<TextureAtlasLayout>,
+    pub texture_atlas: Handle<Image>,
+    /// Team colour masks, packed with the same layout as `texture_atlas`.
+    pub team_mask: Option<Handle<Image>>,
+}
+
+/// Where a frame ended up after packing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub struct FrameLocation {
+    pub page: usize,
+    pub index: usize,
+}
+
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component)]
 pub struct MyAnimationClip {
     pub len: usize,
     pub fps: f32,
-    pub texture_atlas_layout_handle: Handle<TextureAtlasLayout>,
-    pub texture_atlas: Handle<Image>,
+    pub pages: Vec<AtlasPage>,
+    pub frame_locations: Vec<FrameLocation>,
+    pub frame_size: UVec2,
+    /// Region of the untrimmed frame each atlas entry was cut from.
+    pub frame_rects: Vec<URect>,
+    pub metadata: SpriteMetadata,
+}
+impl MyAnimationClip {
+    /// The atlas image and atlas index to display `frame` with.
+    pub fn atlas_for_frame(&self, frame: usize) -> (Handle<Image>, TextureAtlas) {
+        let location = self.frame_locations[frame % self.len];
+        let page = &self.pages[location.page];
+        (
+            page.texture_atlas.clone(),
+            TextureAtlas {
+                layout: page.texture_atlas_layout_handle.clone(),
+                index: location.index,
+            },
+        )
+    }
+    pub fn anchor(&self) -> Anchor {
+        self.metadata
+            .pivot
+            .map(Anchor::Custom)
+            .unwrap_or(Anchor::Center)
+    }
+    pub fn scale(&self) -> f32 {
+        self.metadata.scale.unwrap_or(1.0)
+    }
+    /// Size of a frame in world units after applying the clip scale.
+    pub fn display_size(&self) -> Vec2 {
+        self.frame_size.as_vec2() * self.scale()
+    }
+    /// The clip hitbox in the entity's local space (y up, origin at the pivot).
+    /// Multiply by the entity's `GlobalTransform` to get a world space box for picking.
+    pub fn local_hitbox(&self) -> Option<Rect> {
+        let hitbox = self.metadata.hitbox?;
+        let frame_size = self.frame_size.as_vec2().max(Vec2::ONE);
+        let pivot = self.anchor().as_vec();
+        let display_size = self.display_size();
+        let to_local = |pixel: Vec2| {
+            let normalized = Vec2::new(pixel.x / frame_size.x - 0.5, 0.5 - pixel.y / frame_size.y);
+            (normalized - pivot) * display_size
+        };
+        Some(Rect::from_corners(
+            to_local(hitbox.min),
+            to_local(hitbox.max),
+        ))
+    }
+    /// Anchor for a trimmed frame that keeps the clip pivot at the same spot
+    /// it would have in the untrimmed frame.
+    pub fn frame_anchor(&self, index: usize) -> Anchor {
+        let Some(rect) = self.frame_rects.get(index) else {
+            return self.anchor();
+        };
+        let pivot = self.anchor().as_vec();
+        let frame_size = self.frame_size.as_vec2();
+        let pivot_pixel = Vec2::new(
+            (pivot.x + 0.5) * frame_size.x,
+            (0.5 - pivot.y) * frame_size.y,
+        ) - rect.min.as_vec2();
+        let size = rect.size().as_vec2().max(Vec2::ONE);
+        Anchor::Custom(Vec2::new(
+            pivot_pixel.x / size.x - 0.5,
+            0.5 - pivot_pixel.y / size.y,
+        ))
+    }
+    pub fn frame_display_size(&self, index: usize) -> Vec2 {
+        self.frame_rects
+            .get(index)
+            .map(|rect| rect.size().as_vec2() * self.scale())
+            .unwrap_or_else(|| self.display_size())
+    }
+    pub fn apply_frame(&self, index: usize, sprite: &mut Sprite) {
+        sprite.anchor = self.frame_anchor(index);
+        sprite.custom_size = self.metadata.scale.map(|_| self.frame_display_size(index));
+    }
 }
 
 impl<T: AnimationTypes> From<&AnimationLoadData<T>> for AnimationWithHandles<T> {
@@ -36,18 +143,22 @@ impl<T: AnimationTypes> From<&AnimationLoadData<T>> for AnimationWithHandles<T>
             animation: data.animation.clone(),
             rotation: data.rotation.clone(),
             frames: Vec::new(),
+            masks: Vec::new(),
             fps: data.fps,
+            metadata: data.metadata.clone(),
         }
     }
 }
 #[derive(Resource, Default)]
 pub struct AnimationLibrary<T: AnimationTypes> {
     pub animations: HashMap<AnimationKey<T>, MyAnimationClip>,
+    pub trim_stats: TrimStats,
 }
 impl<T: AnimationTypes> AnimationLibrary<T> {
     pub fn new() -> Self {
         Self {
             animations: HashMap::new(),
+            trim_stats: TrimStats::default(),
         }
     }
     pub fn get_animation(&self, key: &AnimationKey<T>) -> Option<&MyAnimationClip> {
@@ -90,41 +201,105 @@ impl<T: AnimationTypes> AnimationWithPathsToHandles<T> {
     }
     pub fn build_animation_library(
         &self,
+        atlas_settings: &AnimationAtlasSettings<T>,
         mut textures: ResMut<Assets<Image>>,
         mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     ) -> AnimationLibrary<T> {
         // Create a temporary vector to store our results
         let mut results = Vec::new();
+        let mut trim_stats = TrimStats::default();
 
         // Process animations and collect results
-        for animations_opt in self.paths_to_handles.values() {
-            if let Some(animations) = animations_opt {
-                for animation in animations {
-                    let key = AnimationKey {
-                        character: animation.character.clone(),
-                        animation: animation.animation.clone(),
-                        rotation: animation.rotation.clone(),
-                    };
-
-                    // Create texture atlas outside the closure
-                    let (texture_atlas_layout, texture_atlas) =
-                        create_texture_atlas(animation.frames.clone(), None, None, &mut textures);
-                    let texture_atlas_layout_handle =
-                        texture_atlas_layouts.add(texture_atlas_layout);
-                    let clip = MyAnimationClip {
-                        len: animation.frames.len(),
-                        fps: animation.fps,
-                        texture_atlas_layout_handle,
-                        texture_atlas,
-                    };
+        for animation in self.paths_to_handles.values().flatten().flatten() {
+            let key = AnimationKey {
+                character: animation.character.clone(),
+                animation: animation.animation.clone(),
+                rotation: animation.rotation.clone(),
+            };
 
-                    results.push((key, clip));
+            let frame_size = animation
+                .frames
+                .first()
+                .and_then(|frame| textures.get(frame))
+                .map(|image| image.size())
+                .unwrap_or_default();
+
+            let settings = atlas_settings.for_character(&animation.character);
+            let mut frames = match trim_frames(
+                &animation.frames,
+                &animation.masks,
+                &textures,
+                settings.trim,
+                &mut trim_stats,
+            ) {
+                Ok(frames) => frames,
+                Err(e) => {
+                    error!(
+                        "Could not load {} {} at rotation {}: {}",
+                        ron_label(&key.character),
+                        ron_label(&key.animation),
+                        ron_label(&key.rotation),
+                        e
+                    );
+                    continue;
+                }
+            };
+            if let Some(TeamColorMask::KeyColor {
+                hue,
+                tolerance,
+                min_saturation,
+            }) = animation.metadata.team_mask
+            {
+                for frame in frames.iter_mut() {
+                    frame.mask = Some(key_color_mask(&frame.image, hue, tolerance, min_saturation));
                 }
             }
+
+            // Create texture atlas outside the closure
+            let (pages, frame_locations, frame_rects) = match create_texture_atlas_pages(
+                &frames,
+                &settings,
+                &mut textures,
+                &mut texture_atlas_layouts,
+            ) {
+                Ok(pages) => pages,
+                Err(e) => {
+                    error!(
+                        "Could not pack {} {} at rotation {}: {}",
+                        ron_label(&key.character),
+                        ron_label(&key.animation),
+                        ron_label(&key.rotation),
+                        e
+                    );
+                    continue;
+                }
+            };
+            if frame_locations.is_empty() {
+                continue;
+            }
+            let clip = MyAnimationClip {
+                len: frame_locations.len(),
+                fps: animation.fps,
+                pages,
+                frame_locations,
+                frame_size,
+                frame_rects,
+                metadata: animation.metadata.clone(),
+            };
+
+            results.push((key, clip));
         }
 
+        info!(
+            "Trimmed animation frames from {} KiB to {} KiB ({} KiB saved)",
+            trim_stats.untrimmed_bytes / 1024,
+            trim_stats.trimmed_bytes / 1024,
+            trim_stats.saved_bytes() / 1024
+        );
+
         AnimationLibrary {
             animations: results.into_iter().collect(),
+            trim_stats,
         }
     }
 }
@@ -142,45 +317,130 @@ pub fn are_all_animation_sprites_loaded<T: AnimationTypes>(
 
             let animations_with_handles = animations_with_handles_option.as_ref().unwrap();
             animations_with_handles.iter().all(|animation| {
-                animation.frames.iter().all(|frame_handle| {
+                let frames_loaded = animation.frames.iter().all(|frame_handle| {
                     let state_option = asset_server.get_load_state(frame_handle);
                     let state = state_option.unwrap_or(LoadState::NotLoaded);
                     state == LoadState::Loaded
-                })
+                });
+                // Missing masks only disable team colours for the clip
+                let masks_loaded = animation.masks.iter().all(|mask_handle| {
+                    matches!(
+                        asset_server.get_load_state(mask_handle),
+                        Some(LoadState::Loaded | LoadState::Failed(_))
+                    )
+                });
+                frames_loaded && masks_loaded
             })
         })
 }
 
-pub fn create_texture_atlas(
-    handles: Vec<Handle<Image>>,
-    padding: Option<UVec2>,
-    sampling: Option<ImageSampler>,
-    textures: &mut ResMut<Assets<Image>>,
-) -> (TextureAtlasLayout, Handle<Image>) {
+fn pack_images<'a>(
+    images: impl Iterator<Item = &'a Image>,
+    settings: &AtlasSettings,
+) -> Result<(TextureAtlasLayout, Image), TextureAtlasBuilderError> {
     // Build a texture atlas using the individual sprites
     let mut texture_atlas_builder = TextureAtlasBuilder::default();
-    texture_atlas_builder.padding(padding.unwrap_or_default());
-    for handle in handles.iter() {
-        let id = handle.id();
-        let Some(texture) = textures.get(id) else {
-            warn!(
-                "{:?} did not resolve to an `Image` asset.",
-                handle.path().unwrap()
-            );
-            continue;
-        };
+    texture_atlas_builder.padding(settings.padding);
+    for image in images {
+        texture_atlas_builder.add_texture(None, image);
+    }
+    // The builder grows from its initial size and gives up once it passes the max size
+    texture_atlas_builder.initial_size(UVec2::splat(256).min(settings.max_size));
+    texture_atlas_builder.max_size(settings.max_size);
+    let (texture_atlas_layout, mut texture) = texture_atlas_builder.build()?;
+    if settings.generate_mips {
+        generate_mips(&mut texture);
+    }
+    // Update the sampling settings of the texture atlas
+    texture.sampler = settings.sampler();
+
+    Ok((texture_atlas_layout, texture))
+}
 
-        texture_atlas_builder.add_texture(Some(id), texture);
+/// Packs `frames` into one atlas, plus a second atlas with the same layout for their team colour masks.
+pub fn create_texture_atlas(
+    frames: &[TrimmedFrame],
+    settings: &AtlasSettings,
+    textures: &mut Assets<Image>,
+) -> Result<(TextureAtlasLayout, Handle<Image>, Option<Handle<Image>>), TextureAtlasBuilderError> {
+    let (texture_atlas_layout, texture) =
+        pack_images(frames.iter().map(|frame| &frame.image), settings)?;
+
+    let mut team_mask = None;
+    if !frames.is_empty() && frames.iter().all(|frame| frame.mask.is_some()) {
+        // Identical sizes in identical order pack into an identical layout
+        match pack_images(
+            frames.iter().filter_map(|frame| frame.mask.as_ref()),
+            settings,
+        ) {
+            Ok((mask_layout, mask)) if mask_layout.textures == texture_atlas_layout.textures => {
+                team_mask = Some(textures.add(mask));
+            }
+            _ => warn!("Team colour masks could not be packed like their frames"),
+        }
     }
-    texture_atlas_builder.max_size(UVec2::new(16384, 16384));
-    let (texture_atlas_layout, texture) = texture_atlas_builder.build().unwrap();
-    let texture = textures.add(texture);
 
-    // Update the sampling settings of the texture atlas
-    let image = textures.get_mut(&texture).unwrap();
-    image.sampler = sampling.unwrap_or_default();
+    Ok((texture_atlas_layout, textures.add(texture), team_mask))
+}
+
+/// The pages of a clip, the location of every frame and the source rect of every frame.
+pub type AtlasPages = (Vec<AtlasPage>, Vec<FrameLocation>, Vec<URect>);
 
-    (texture_atlas_layout, texture)
+/// A frame too large for any atlas page. Leaving it out would make the clip shorter than
+/// its other rotations, so the whole clip fails instead.
+#[derive(Debug, Error)]
+#[error("Frame {frame} of size {size} does not fit into an atlas of {max_size}: {source}")]
+pub struct FrameTooLargeError {
+    pub frame: usize,
+    pub size: UVec2,
+    pub max_size: UVec2,
+    source: TextureAtlasBuilderError,
+}
+
+/// Packs `frames` into as many atlas pages as needed to stay within `settings.max_size`.
+pub fn create_texture_atlas_pages(
+    frames: &[TrimmedFrame],
+    settings: &AtlasSettings,
+    textures: &mut Assets<Image>,
+    texture_atlas_layouts: &mut Assets<TextureAtlasLayout>,
+) -> Result<AtlasPages, FrameTooLargeError> {
+    let mut pages = Vec::new();
+    let mut frame_locations = Vec::new();
+    let mut frame_rects = Vec::new();
+    let mut start = 0;
+    while start < frames.len() {
+        let mut end = frames.len();
+        loop {
+            match create_texture_atlas(&frames[start..end], settings, textures) {
+                Ok((texture_atlas_layout, texture_atlas, team_mask)) => {
+                    let page = pages.len();
+                    pages.push(AtlasPage {
+                        texture_atlas_layout_handle: texture_atlas_layouts
+                            .add(texture_atlas_layout),
+                        texture_atlas,
+                        team_mask,
+                    });
+                    for (index, frame) in frames[start..end].iter().enumerate() {
+                        frame_locations.push(FrameLocation { page, index });
+                        frame_rects.push(frame.rect);
+                    }
+                    start = end;
+                    break;
+                }
+                // Try again with half of the remaining frames on this page
+                Err(_) if end - start > 1 => end = start + (end - start) / 2,
+                Err(source) => {
+                    return Err(FrameTooLargeError {
+                        frame: start,
+                        size: frames[start].image.size(),
+                        max_size: settings.max_size,
+                        source,
+                    })
+                }
+            }
+        }
+    }
+    Ok((pages, frame_locations, frame_rects))
 }
 
 pub fn load_sprites<T: AnimationTypes>(
@@ -211,6 +471,13 @@ pub fn load_sprites<T: AnimationTypes>(
                     .iter()
                     .map(|path| asset_server.load(path))
                     .collect();
+                if animation.metadata.team_mask == Some(TeamColorMask::MaskFrames) {
+                    animation_with_handles.masks = animation
+                        .frames
+                        .iter()
+                        .map(|path| asset_server.load(TeamColorMask::mask_path(path)))
+                        .collect();
+                }
                 animation_with_handles
             })
             .collect();
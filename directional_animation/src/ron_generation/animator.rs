@@ -2,76 +2,110 @@ use bevy::prelude::*;
 
 use super::{
     animation_library::{AnimationLibrary, MyAnimationClip},
+    facing::Facing,
     AnimationTypes,
 };
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct MovementDirection {
     pub direction: Vec3,
 }
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct AnimationTimer {
     pub timer: Timer,
     pub current_frame: usize,
 }
 
+/// Animation shown, held on its first frame, when a character has no clip for the requested one.
+/// Without it the entity keeps playing its previous clip.
+#[derive(Resource)]
+pub struct FallbackAnimation<T: AnimationTypes>(pub T::AnimationName);
+
 pub fn change_animation<T: AnimationTypes>(
     mut commands: Commands,
-    query: Query<
+    mut query: Query<
         (
             Entity,
             &T::CharacterName,
             &T::AnimationName,
             &MovementDirection,
+            Option<&Facing>,
             Option<&AnimationTimer>,
+            Option<&mut Sprite>,
         ),
         Or<(
             Changed<T::CharacterName>,
             Changed<T::AnimationName>,
             Changed<MovementDirection>,
+            Changed<Facing>,
         )>,
     >,
     animation_library: Res<AnimationLibrary<T>>,
+    fallback: Option<Res<FallbackAnimation<T>>>,
 ) {
-    for (entity, character, animation, movement_direction, timer) in query.iter() {
-        if let Some(animation_clip) =
-            animation_library.find_animation(character, animation, movement_direction.direction)
-        {
-            let mut index = 0;
-            if let Some(timer) = timer {
-                index = timer.current_frame.clone();
-            }
-            commands.entity(entity).insert(animation_clip.clone());
-            commands
-                .entity(entity)
-                .insert(animation_clip.texture_atlas.clone());
-            commands.entity(entity).insert(TextureAtlas {
-                layout: animation_clip.texture_atlas_layout_handle.clone(),
-                index,
+    for (entity, character, animation, movement_direction, facing, timer, sprite) in
+        query.iter_mut()
+    {
+        let direction = facing.map_or(movement_direction.direction, |facing| facing.direction);
+        let animation_clip = animation_library
+            .find_animation(character, animation, direction)
+            .cloned()
+            .or_else(|| {
+                let fallback = fallback.as_ref()?;
+                let mut clip = animation_library
+                    .find_animation(character, &fallback.0, direction)?
+                    .clone();
+                clip.len = 1;
+                Some(clip)
             });
+        let Some(animation_clip) = animation_clip else {
+            continue;
+        };
+        let mut index = 0;
+        if let Some(timer) = timer {
+            index = timer.current_frame % animation_clip.len;
+        }
+        let (texture, atlas) = animation_clip.atlas_for_frame(index);
+        commands.entity(entity).insert(texture);
+        commands.entity(entity).insert(atlas);
+        if let Some(mut sprite) = sprite {
+            animation_clip.apply_frame(index, &mut sprite);
+        }
 
-            if timer.is_none() {
-                commands.entity(entity).insert(AnimationTimer {
-                    timer: Timer::from_seconds(1.0 / animation_clip.fps, TimerMode::Repeating),
-                    current_frame: index,
-                });
-            }
+        if timer.is_none() {
+            commands.entity(entity).insert(AnimationTimer {
+                timer: Timer::from_seconds(1.0 / animation_clip.fps, TimerMode::Repeating),
+                current_frame: index,
+            });
         }
+        commands.entity(entity).insert(animation_clip);
     }
 }
 pub fn animate(
     time: Res<Time>,
-    mut query: Query<(&MyAnimationClip, &mut AnimationTimer, &mut TextureAtlas)>,
+    mut query: Query<(
+        &MyAnimationClip,
+        &mut AnimationTimer,
+        &mut TextureAtlas,
+        &mut Handle<Image>,
+        Option<&mut Sprite>,
+    )>,
 ) {
-    for (clip, mut timer, mut atlas) in &mut query {
+    for (clip, mut timer, mut atlas, mut texture, sprite) in &mut query {
         timer.timer.tick(time.delta());
         if timer.timer.just_finished() {
-            timer.current_frame = if timer.current_frame == clip.len - 1 {
-                0
-            } else {
-                timer.current_frame + 1
-            };
-            atlas.index = timer.current_frame;
+            timer.current_frame = (timer.current_frame + 1) % clip.len;
+            let (page_texture, page_atlas) = clip.atlas_for_frame(timer.current_frame);
+            // Only swap the page when the clip spills over several atlases
+            if *texture != page_texture {
+                *texture = page_texture;
+            }
+            *atlas = page_atlas;
+            if let Some(mut sprite) = sprite {
+                clip.apply_frame(timer.current_frame, &mut sprite);
+            }
         }
     }
 }
@@ -0,0 +1,151 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::TextureFormat,
+        texture::{ImageFilterMode, ImageSampler, ImageSamplerDescriptor},
+    },
+    utils::HashMap,
+};
+
+use super::AnimationTypes;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AtlasSampling {
+    /// Use the sampler configured on `ImagePlugin`.
+    #[default]
+    Default,
+    /// Crisp pixels, for pixel art.
+    Nearest,
+    Linear,
+}
+
+/// How the frames of a clip are packed into atlas pages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasSettings {
+    /// Empty pixels between packed frames, prevents neighbouring frames from bleeding in.
+    pub padding: UVec2,
+    pub sampling: AtlasSampling,
+    pub generate_mips: bool,
+    /// Largest page size. Clips that do not fit are split over several pages.
+    pub max_size: UVec2,
+    /// Crop frames to their opaque bounds before packing.
+    pub trim: bool,
+}
+
+impl Default for AtlasSettings {
+    fn default() -> Self {
+        Self {
+            padding: UVec2::ZERO,
+            sampling: AtlasSampling::Default,
+            generate_mips: false,
+            max_size: UVec2::new(16384, 16384),
+            trim: true,
+        }
+    }
+}
+
+impl AtlasSettings {
+    pub fn sampler(&self) -> ImageSampler {
+        let mut descriptor = match self.sampling {
+            AtlasSampling::Default if !self.generate_mips => return ImageSampler::Default,
+            AtlasSampling::Default | AtlasSampling::Linear => ImageSamplerDescriptor::linear(),
+            AtlasSampling::Nearest => ImageSamplerDescriptor::nearest(),
+        };
+        if self.generate_mips {
+            descriptor.mipmap_filter = ImageFilterMode::Linear;
+        }
+        ImageSampler::Descriptor(descriptor)
+    }
+}
+
+/// WebGL2 only guarantees 4096 pixel textures, assume that until the render device tells us more.
+#[cfg(target_arch = "wasm32")]
+const FALLBACK_TEXTURE_SIZE_LIMIT: Option<u32> = Some(4096);
+#[cfg(not(target_arch = "wasm32"))]
+const FALLBACK_TEXTURE_SIZE_LIMIT: Option<u32> = None;
+
+/// Atlas settings used while building the [`AnimationLibrary`](super::animation_library::AnimationLibrary),
+/// with optional overrides per character.
+#[derive(Resource)]
+pub struct AnimationAtlasSettings<T: AnimationTypes> {
+    pub default: AtlasSettings,
+    pub per_character: HashMap<T::CharacterName, AtlasSettings>,
+    /// Largest texture the render device supports, every page is clamped to it.
+    /// Filled from the `RenderDevice` limits before the library is built.
+    pub texture_size_limit: Option<u32>,
+}
+
+impl<T: AnimationTypes> Default for AnimationAtlasSettings<T> {
+    fn default() -> Self {
+        Self {
+            default: AtlasSettings::default(),
+            per_character: HashMap::new(),
+            texture_size_limit: FALLBACK_TEXTURE_SIZE_LIMIT,
+        }
+    }
+}
+
+impl<T: AnimationTypes> Clone for AnimationAtlasSettings<T> {
+    fn clone(&self) -> Self {
+        Self {
+            default: self.default.clone(),
+            per_character: self.per_character.clone(),
+            texture_size_limit: self.texture_size_limit,
+        }
+    }
+}
+
+impl<T: AnimationTypes> AnimationAtlasSettings<T> {
+    pub fn for_character(&self, character: &T::CharacterName) -> AtlasSettings {
+        let mut settings = self
+            .per_character
+            .get(character)
+            .unwrap_or(&self.default)
+            .clone();
+        if let Some(limit) = self.texture_size_limit {
+            settings.max_size = settings.max_size.min(UVec2::splat(limit));
+        }
+        settings
+    }
+}
+
+/// Appends a box filtered mip chain to an 8 bit RGBA image.
+pub fn generate_mips(image: &mut Image) {
+    match image.texture_descriptor.format {
+        TextureFormat::Rgba8Unorm
+        | TextureFormat::Rgba8UnormSrgb
+        | TextureFormat::Bgra8Unorm
+        | TextureFormat::Bgra8UnormSrgb => {}
+        format => {
+            warn!("Can not generate mips for {:?} atlases", format);
+            return;
+        }
+    }
+    let mut size = image.size();
+    let mut level = image.data.clone();
+    let mut data = image.data.clone();
+    let mut mip_level_count = 1;
+    while size.x > 1 || size.y > 1 {
+        let next_size = (size / 2).max(UVec2::ONE);
+        let mut next = Vec::with_capacity((next_size.x * next_size.y * 4) as usize);
+        for y in 0..next_size.y {
+            for x in 0..next_size.x {
+                for channel in 0..4 {
+                    let mut sum = 0u32;
+                    for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                        let sx = (x * 2 + dx).min(size.x - 1);
+                        let sy = (y * 2 + dy).min(size.y - 1);
+                        sum += level[((sy * size.x + sx) * 4 + channel) as usize] as u32;
+                    }
+                    next.push((sum / 4) as u8);
+                }
+            }
+        }
+        data.extend_from_slice(&next);
+        level = next;
+        size = next_size;
+        mip_level_count += 1;
+    }
+    image.data = data;
+    image.texture_descriptor.mip_level_count = mip_level_count;
+}
@@ -0,0 +1,162 @@
+//! Compact binary encoding of an [`AnimationsCollection`], for builds where parsing RON is too slow.
+//! The generator writes it next to the `.anim.ron` as `.anim.bin`, and it loads the same way.
+//!
+//! The encoding is bincode of the same clips the RON stores, behind a magic number and the
+//! [`ANIMATIONS_FORMAT_VERSION`]. Unlike RON it can't skip unknown fields,
+//! so manifests are regenerated rather than migrated.
+//! Characters and animations are stored as variant indices, which only the game's own
+//! [`AnimationTypes`] can read back, not the [`dynamic`](super::dynamic) ones.
+
+use bevy::{
+    asset::{AssetLoader, AsyncReadExt},
+    math::{Rect, Vec2},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    format::{AnimationsHeader, ANIMATIONS_FORMAT_VERSION},
+    team_color::TeamColorMask,
+    AnimationLoadData, AnimationLoaderError, AnimationTypes, AnimationsCollection, SpriteMetadata,
+};
+
+const MAGIC: [u8; 4] = *b"ANIM";
+
+/// [`SpriteMetadata`] without skipped fields, which bincode can't tell apart.
+#[derive(Serialize, Deserialize)]
+struct BinaryMetadata {
+    pivot: Option<Vec2>,
+    scale: Option<f32>,
+    hitbox: Option<Rect>,
+    team_mask: Option<TeamColorMask>,
+}
+
+impl From<SpriteMetadata> for BinaryMetadata {
+    fn from(metadata: SpriteMetadata) -> Self {
+        Self {
+            pivot: metadata.pivot,
+            scale: metadata.scale,
+            hitbox: metadata.hitbox,
+            team_mask: metadata.team_mask,
+        }
+    }
+}
+
+impl From<BinaryMetadata> for SpriteMetadata {
+    fn from(metadata: BinaryMetadata) -> Self {
+        Self {
+            pivot: metadata.pivot,
+            scale: metadata.scale,
+            hitbox: metadata.hitbox,
+            team_mask: metadata.team_mask,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryClip<T: AnimationTypes> {
+    character: T::CharacterName,
+    animation: T::AnimationName,
+    rotation: T::Rotation,
+    frames: Vec<String>,
+    fps: f32,
+    metadata: BinaryMetadata,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct BinaryManifest<T: AnimationTypes> {
+    fps: f32,
+    base_path: String,
+    metadata: BinaryMetadata,
+    clips: Vec<BinaryClip<T>>,
+}
+
+/// Encodes `collection` as a `.anim.bin`.
+pub fn to_binary<T: AnimationTypes>(
+    collection: &AnimationsCollection<T>,
+) -> Result<Vec<u8>, AnimationLoaderError> {
+    let (base_path, animations) = collection.stored_animations();
+    let manifest = BinaryManifest::<T> {
+        fps: collection.header.fps,
+        base_path,
+        metadata: collection.header.metadata.clone().into(),
+        clips: animations
+            .into_iter()
+            .map(|animation| BinaryClip {
+                character: animation.character,
+                animation: animation.animation,
+                rotation: animation.rotation,
+                frames: animation.frames,
+                fps: animation.fps,
+                metadata: animation.metadata.into(),
+            })
+            .collect(),
+    };
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend(ANIMATIONS_FORMAT_VERSION.to_le_bytes());
+    bincode::serialize_into(&mut bytes, &manifest)?;
+    Ok(bytes)
+}
+
+/// Decodes a `.anim.bin` written by [`to_binary`].
+pub fn from_binary<T: AnimationTypes>(
+    bytes: &[u8],
+) -> Result<AnimationsCollection<T>, AnimationLoaderError> {
+    let (magic, rest) = bytes.split_at(MAGIC.len().min(bytes.len()));
+    if magic != MAGIC || rest.len() < 4 {
+        return Err(AnimationLoaderError::NotABinaryManifest);
+    }
+    let (version, rest) = rest.split_at(4);
+    let version = u32::from_le_bytes(version.try_into().unwrap());
+    if version != ANIMATIONS_FORMAT_VERSION {
+        return Err(AnimationLoaderError::UnsupportedVersion(version));
+    }
+    let manifest: BinaryManifest<T> = bincode::deserialize(rest)?;
+    let header = AnimationsHeader {
+        version,
+        fps: manifest.fps,
+        base_path: manifest.base_path,
+        metadata: manifest.metadata.into(),
+    };
+    let animations = manifest
+        .clips
+        .into_iter()
+        .map(|clip| AnimationLoadData {
+            character: clip.character,
+            animation: clip.animation,
+            rotation: clip.rotation,
+            frames: clip.frames,
+            fps: clip.fps,
+            metadata: clip.metadata.into(),
+        })
+        .collect();
+    Ok(AnimationsCollection::resolve(header, animations))
+}
+
+#[derive(Default)]
+pub struct BinaryAnimationLoader<T: AnimationTypes> {
+    phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: AnimationTypes> AssetLoader for BinaryAnimationLoader<T> {
+    fn extensions(&self) -> &[&str] {
+        &["anim.bin"]
+    }
+
+    type Asset = AnimationsCollection<T>;
+
+    type Settings = ();
+
+    type Error = AnimationLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut bevy::asset::io::Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut bevy::asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        from_binary(&bytes)
+    }
+}
@@ -0,0 +1,131 @@
+//! [`AnimationTypes`] that read any `.anim.ron` without knowing the game's enums,
+//! for tools like the viewer. Character and animation variants are kept as their names,
+//! rotations are read as degrees in the convention chosen by the type's parameters.
+
+use bevy::{prelude::*, utils::HashSet};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+use super::{
+    rotation::{AngleConvention, AngleRotation, Winding},
+    AnimationTypes,
+};
+
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DynamicCharacter(pub String);
+
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DynamicAnimation(pub String);
+
+/// Rotations are [`AngleRotation`]s whose 0 degrees is `ZERO` degrees counter-clockwise from
+/// the right of the screen, growing clockwise when `CLOCKWISE`. The defaults match the game's
+/// files, 0 faces down and angles grow counter-clockwise.
+#[derive(TypePath, Default, PartialEq, Eq, Hash)]
+pub struct DynamicAnimationTypes<const ZERO: u32 = 270, const CLOCKWISE: bool = false>;
+
+impl<const ZERO: u32, const CLOCKWISE: bool> AnimationTypes
+    for DynamicAnimationTypes<ZERO, CLOCKWISE>
+{
+    type CharacterName = DynamicCharacter;
+    type AnimationName = DynamicAnimation;
+    type Rotation = AngleRotation<DynamicAngles<ZERO, CLOCKWISE>>;
+}
+
+/// The [`AngleConvention`] of [`DynamicAnimationTypes`].
+pub struct DynamicAngles<const ZERO: u32, const CLOCKWISE: bool>;
+
+impl<const ZERO: u32, const CLOCKWISE: bool> AngleConvention for DynamicAngles<ZERO, CLOCKWISE> {
+    const ZERO_ANGLE: f32 = ZERO as f32;
+    const WINDING: Winding = if CLOCKWISE {
+        Winding::Clockwise
+    } else {
+        Winding::CounterClockwise
+    };
+}
+
+// Variant names are written as unit variants, which need a `'static` name.
+// Interning keeps that to one allocation per distinct name.
+fn intern(name: &str) -> &'static str {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut names = NAMES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(name) = names.get(name) {
+        return name;
+    }
+    let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+    names.insert(name);
+    name
+}
+
+/// Name of a unit variant. RON reads enum variants through `deserialize_enum`,
+/// and the variant itself as an identifier.
+struct VariantName(String);
+
+impl<'de> Deserialize<'de> for VariantName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(VariantNameVisitor)
+    }
+}
+
+struct VariantNameVisitor;
+
+impl<'de> de::Visitor<'de> for VariantNameVisitor {
+    type Value = VariantName;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an enum variant name")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<VariantName, E> {
+        Ok(VariantName(value.to_string()))
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<VariantName, E> {
+        Ok(VariantName(String::from_utf8_lossy(value).to_string()))
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<VariantName, A::Error> {
+        let (name, variant): (VariantName, _) = data.variant()?;
+        de::VariantAccess::unit_variant(variant)?;
+        Ok(name)
+    }
+}
+
+/// Binary formats store variants by index, which only the game's own enums know.
+const BINARY_UNSUPPORTED: &str =
+    "dynamic names need a self-describing format like RON, binary manifests store variant indices";
+
+macro_rules! variant_name_serde {
+    ($name:ident) => {
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                if !serializer.is_human_readable() {
+                    return Err(ser::Error::custom(BINARY_UNSUPPORTED));
+                }
+                // Self-describing formats write the name and ignore the index
+                serializer.serialize_unit_variant(stringify!($name), 0, intern(&self.0))
+            }
+        }
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                if !deserializer.is_human_readable() {
+                    return Err(de::Error::custom(BINARY_UNSUPPORTED));
+                }
+                deserializer
+                    .deserialize_enum(stringify!($name), &[], VariantNameVisitor)
+                    .map(|name| $name(name.0))
+            }
+        }
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+variant_name_serde!(DynamicCharacter);
+variant_name_serde!(DynamicAnimation);
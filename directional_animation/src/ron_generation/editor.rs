@@ -0,0 +1,138 @@
+//! `bevy_editor_pls` window listing the [`AnimationLibrary`] with a live preview of every rotation.
+//! Add it with `app.add_editor_window::<AnimationLibraryWindow<T>>()`.
+
+use bevy::prelude::*;
+use bevy_editor_pls::{
+    editor_window::{EditorWindow, EditorWindowContext},
+    egui,
+};
+use bevy_egui::EguiUserTextures;
+use std::marker::PhantomData;
+
+use super::{animation_library::AnimationLibrary, ron_label, AnimationTypes};
+
+pub struct AnimationLibraryWindow<T: AnimationTypes> {
+    phantom: PhantomData<T>,
+}
+
+pub struct AnimationLibraryWindowState {
+    /// RON of the selected character and animation.
+    pub selected: Option<(String, String)>,
+    pub speed: f32,
+    pub preview_size: f32,
+}
+
+impl Default for AnimationLibraryWindowState {
+    fn default() -> Self {
+        Self {
+            selected: None,
+            speed: 1.0,
+            preview_size: 96.0,
+        }
+    }
+}
+
+struct RotationPreview {
+    label: String,
+    texture: Handle<Image>,
+    uv: egui::Rect,
+    aspect: f32,
+}
+
+impl<T: AnimationTypes> EditorWindow for AnimationLibraryWindow<T> {
+    type State = AnimationLibraryWindowState;
+    const NAME: &'static str = "Animations";
+
+    fn ui(world: &mut World, mut cx: EditorWindowContext, ui: &mut egui::Ui) {
+        let Some(state) = cx.state_mut::<Self>() else {
+            return;
+        };
+        let Some(library) = world.get_resource::<AnimationLibrary<T>>() else {
+            ui.label("Animations are not loaded");
+            return;
+        };
+        let mut clips: Vec<(String, String)> = library
+            .animations
+            .keys()
+            .map(|key| (ron_label(&key.character), ron_label(&key.animation)))
+            .collect();
+        clips.sort();
+        clips.dedup();
+        ui.label(format!(
+            "{} clips, {} KiB saved by trimming",
+            library.animations.len(),
+            library.trim_stats.saved_bytes() / 1024
+        ));
+
+        ui.horizontal(|ui| {
+            ui.add(egui::Slider::new(&mut state.speed, 0.0..=4.0).text("speed"));
+            ui.add(egui::Slider::new(&mut state.preview_size, 32.0..=256.0).text("size"));
+        });
+        ui.separator();
+
+        // Everything the previews need, collected before borrowing the world mutably
+        let elapsed = world.resource::<Time>().elapsed_seconds() * state.speed;
+        let layouts = world.resource::<Assets<TextureAtlasLayout>>();
+        let mut previews = Vec::new();
+        for (character, animation) in clips.iter() {
+            let selected = state.selected.as_ref() == Some(&(character.clone(), animation.clone()));
+            if ui
+                .selectable_label(selected, format!("{} {}", character, animation))
+                .clicked()
+            {
+                state.selected = Some((character.clone(), animation.clone()));
+            }
+        }
+        if let Some((character, animation)) = &state.selected {
+            for (key, clip) in library.animations.iter() {
+                if ron_label(&key.character) != *character
+                    || ron_label(&key.animation) != *animation
+                {
+                    continue;
+                }
+                let frame = (elapsed * clip.fps) as usize % clip.len.max(1);
+                let location = clip.frame_locations[frame];
+                let page = &clip.pages[location.page];
+                let Some(layout) = layouts.get(&page.texture_atlas_layout_handle) else {
+                    continue;
+                };
+                let rect = layout.textures[location.index].as_rect();
+                let size = layout.size.as_vec2();
+                previews.push(RotationPreview {
+                    label: ron_label(&key.rotation),
+                    texture: page.texture_atlas.clone_weak(),
+                    uv: egui::Rect::from_min_max(
+                        egui::pos2(rect.min.x / size.x, rect.min.y / size.y),
+                        egui::pos2(rect.max.x / size.x, rect.max.y / size.y),
+                    ),
+                    aspect: rect.width() / rect.height().max(1.0),
+                });
+            }
+        }
+        previews.sort_by(|a, b| a.label.cmp(&b.label));
+
+        let preview_size = state.preview_size;
+        let mut textures = world.resource_mut::<EguiUserTextures>();
+        ui.separator();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for preview in previews {
+                    let texture = textures.add_image(preview.texture);
+                    let size = if preview.aspect >= 1.0 {
+                        egui::vec2(preview_size, preview_size / preview.aspect)
+                    } else {
+                        egui::vec2(preview_size * preview.aspect, preview_size)
+                    };
+                    ui.vertical(|ui| {
+                        ui.add(
+                            egui::Image::new(egui::load::SizedTexture::new(texture, size))
+                                .uv(preview.uv),
+                        );
+                        ui.label(preview.label);
+                    });
+                }
+            });
+        });
+        ui.ctx().request_repaint();
+    }
+}
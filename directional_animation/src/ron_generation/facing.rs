@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+
+use super::animator::MovementDirection;
+
+/// Direction the rotations are matched against, resolved every frame from the
+/// [`MovementDirection`] or a [`FacingTarget`].
+/// Keeps the last facing while the entity stands still.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Facing {
+    pub direction: Vec3,
+}
+
+/// Faces something other than the movement direction, e.g. an attack target while strafing.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum FacingTarget {
+    Entity(Entity),
+    Point(Vec3),
+}
+
+/// Camera used to project facing into screen space, needed when more than one camera is active.
+#[derive(Component, Default)]
+pub struct FacingCamera;
+
+#[derive(Resource, Clone, Debug, Default)]
+pub struct FacingSettings {
+    /// Project world space facing onto the view plane of the camera before matching rotations.
+    /// The rotations then see screen space vectors, `+x` right and `+y` up.
+    pub project_through_camera: bool,
+}
+
+/// Screen space direction of a world space vector, ignoring perspective.
+pub fn project_through_camera(camera: &GlobalTransform, direction: Vec3) -> Vec3 {
+    let view = camera.compute_transform().rotation.inverse() * direction;
+    Vec3::new(view.x, view.y, 0.0)
+}
+
+pub fn resolve_facing(
+    mut commands: Commands,
+    settings: Res<FacingSettings>,
+    mut query: Query<(
+        Entity,
+        &MovementDirection,
+        Option<&FacingTarget>,
+        Option<&GlobalTransform>,
+        Option<&mut Facing>,
+    )>,
+    targets: Query<&GlobalTransform>,
+    marked_cameras: Query<&GlobalTransform, With<FacingCamera>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+) {
+    let camera = if settings.project_through_camera {
+        marked_cameras.iter().next().or_else(|| {
+            cameras
+                .iter()
+                .filter(|(camera, _)| camera.is_active)
+                .max_by_key(|(camera, _)| camera.order)
+                .map(|(_, transform)| transform)
+        })
+    } else {
+        None
+    };
+
+    for (entity, movement, target, transform, facing) in query.iter_mut() {
+        let position = transform.map(|transform| transform.translation());
+        let target_position = match target {
+            Some(FacingTarget::Entity(target)) => {
+                targets.get(*target).ok().map(|target| target.translation())
+            }
+            Some(FacingTarget::Point(point)) => Some(*point),
+            None => None,
+        };
+        let mut direction = match (target_position, position) {
+            (Some(target), Some(position)) => target - position,
+            _ => movement.direction,
+        };
+        if direction.length_squared() <= f32::EPSILON {
+            continue;
+        }
+        if let Some(camera) = camera {
+            direction = project_through_camera(camera, direction);
+        }
+
+        match facing {
+            // Only touch the component when it changes, `change_animation` reacts to changes
+            Some(mut facing) => {
+                facing.set_if_neq(Facing { direction });
+            }
+            None => {
+                commands.entity(entity).insert(Facing { direction });
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! On disk layout of `.anim.ron` files.
+//!
+//! Files start with an [`AnimationsHeader`] and list the clips after it:
+//!
+//! ```ron
+//! (
+//!     version: 1,
+//!     fps: 30.0,
+//!     base_path: "wolf",
+//!     metadata: (scale: Some(0.2)),
+//!     animations: [
+//!         (character: Wolf, animation: Running, rotation: (90), frames: ["WOLK/90/frame_0000.png"]),
+//!     ],
+//! )
+//! ```
+//!
+//! Clips leave out the fps and metadata they share with the header,
+//! and their frame paths are relative to `base_path`.
+//! Files written before the header existed are a bare list of clips and are read as version 0.
+//! Fields a file does not know about get their defaults and unknown fields are skipped,
+//! so older files are migrated when they are loaded and rewritten in the current version.
+
+use serde::{
+    de::{self, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::fmt;
+use std::marker::PhantomData;
+
+use super::{AnimationLoadData, AnimationTypes, AnimationsCollection, SpriteMetadata};
+
+/// Version written by this crate.
+pub const ANIMATIONS_FORMAT_VERSION: u32 = 1;
+
+/// Settings shared by every clip of a collection.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationsHeader {
+    /// Version of the file the collection was read from.
+    pub version: u32,
+    /// Used by clips without their own fps, unset when 0.
+    pub fps: f32,
+    /// Prepended to every frame path.
+    pub base_path: String,
+    /// Fills the unset metadata of every clip.
+    pub metadata: SpriteMetadata,
+}
+
+impl Default for AnimationsHeader {
+    fn default() -> Self {
+        Self {
+            version: ANIMATIONS_FORMAT_VERSION,
+            fps: 0.0,
+            base_path: String::new(),
+            metadata: SpriteMetadata::default(),
+        }
+    }
+}
+
+pub(crate) fn is_unset_fps(fps: &f32) -> bool {
+    *fps == 0.0
+}
+
+fn join_asset_path(base_path: &str, path: &str) -> String {
+    if base_path.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}/{}", base_path.trim_end_matches('/'), path)
+    }
+}
+
+impl<T: AnimationTypes> AnimationsCollection<T> {
+    /// Applies the header to clips as they are stored in a file.
+    pub(crate) fn resolve(
+        header: AnimationsHeader,
+        mut animations: Vec<AnimationLoadData<T>>,
+    ) -> Self {
+        for animation in animations.iter_mut() {
+            for frame in animation.frames.iter_mut() {
+                *frame = join_asset_path(&header.base_path, frame);
+            }
+            if is_unset_fps(&animation.fps) {
+                animation.fps = header.fps;
+            }
+            animation.metadata = animation.metadata.or(&header.metadata);
+        }
+        Self { header, animations }
+    }
+
+    /// The clips as they are stored in a file, leaving out what the header provides.
+    /// The inverse of `resolve`.
+    pub(crate) fn stored_animations(&self) -> (String, Vec<AnimationLoadData<T>>) {
+        let header = &self.header;
+        let prefix = format!("{}/", header.base_path.trim_end_matches('/'));
+        // A frame outside the base path can't be written relative to it
+        let base_path = if !header.base_path.is_empty()
+            && self
+                .animations
+                .iter()
+                .flat_map(|animation| animation.frames.iter())
+                .all(|frame| frame.starts_with(&prefix))
+        {
+            header.base_path.clone()
+        } else {
+            String::new()
+        };
+        let animations = self
+            .animations
+            .iter()
+            .map(|animation| AnimationLoadData {
+                character: animation.character.clone(),
+                animation: animation.animation.clone(),
+                rotation: animation.rotation.clone(),
+                frames: animation
+                    .frames
+                    .iter()
+                    .map(|frame| {
+                        if base_path.is_empty() {
+                            frame.clone()
+                        } else {
+                            frame[prefix.len()..].to_string()
+                        }
+                    })
+                    .collect(),
+                fps: if animation.fps == header.fps {
+                    0.0
+                } else {
+                    animation.fps
+                },
+                metadata: if animation.metadata == header.metadata {
+                    SpriteMetadata::default()
+                } else {
+                    animation.metadata.clone()
+                },
+            })
+            .collect();
+        (base_path, animations)
+    }
+}
+
+impl<T: AnimationTypes> Serialize for AnimationsCollection<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (base_path, animations) = self.stored_animations();
+        let mut state = serializer.serialize_struct("AnimationsCollection", 5)?;
+        state.serialize_field("version", &ANIMATIONS_FORMAT_VERSION)?;
+        if is_unset_fps(&self.header.fps) {
+            state.skip_field("fps")?;
+        } else {
+            state.serialize_field("fps", &self.header.fps)?;
+        }
+        if base_path.is_empty() {
+            state.skip_field("base_path")?;
+        } else {
+            state.serialize_field("base_path", &base_path)?;
+        }
+        if self.header.metadata.is_empty() {
+            state.skip_field("metadata")?;
+        } else {
+            state.serialize_field("metadata", &self.header.metadata)?;
+        }
+        state.serialize_field("animations", &animations)?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum HeaderField {
+    Version,
+    Fps,
+    BasePath,
+    Metadata,
+    Animations,
+    #[serde(other)]
+    Unknown,
+}
+
+struct CollectionVisitor<T: AnimationTypes>(PhantomData<T>);
+
+impl<'de, T: AnimationTypes> Visitor<'de> for CollectionVisitor<T> {
+    type Value = AnimationsCollection<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an animation collection or a list of animations")
+    }
+
+    // Version 0, a bare list of clips
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut animations = Vec::new();
+        while let Some(animation) = seq.next_element()? {
+            animations.push(animation);
+        }
+        let header = AnimationsHeader {
+            version: 0,
+            ..Default::default()
+        };
+        Ok(AnimationsCollection::resolve(header, animations))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut header = AnimationsHeader::default();
+        let mut version = None;
+        let mut animations = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                HeaderField::Version => version = Some(map.next_value()?),
+                HeaderField::Fps => header.fps = map.next_value()?,
+                HeaderField::BasePath => header.base_path = map.next_value()?,
+                HeaderField::Metadata => header.metadata = map.next_value()?,
+                HeaderField::Animations => animations = Some(map.next_value()?),
+                HeaderField::Unknown => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        header.version = version.ok_or_else(|| de::Error::missing_field("version"))?;
+        if header.version > ANIMATIONS_FORMAT_VERSION {
+            return Err(de::Error::custom(format!(
+                "animation format version {} is newer than the supported version {}",
+                header.version, ANIMATIONS_FORMAT_VERSION
+            )));
+        }
+        let animations = animations.ok_or_else(|| de::Error::missing_field("animations"))?;
+        Ok(AnimationsCollection::resolve(header, animations))
+    }
+}
+
+impl<'de, T: AnimationTypes> Deserialize<'de> for AnimationsCollection<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // A list is the unversioned format, a struct the versioned one
+        deserializer.deserialize_any(CollectionVisitor(PhantomData))
+    }
+}
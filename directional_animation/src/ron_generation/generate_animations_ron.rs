@@ -1,4 +1,5 @@
 use super::*;
+use std::io;
 
 //recursively traverses all folders. the root folder contains character folders
 //next level name is character name, use alias to get the character name
@@ -7,6 +8,15 @@ use super::*;
 //the rotation folder contains a bunch of png files. extract file paths, sort them with natural sort(alphabetically)
 // the final ron is list (character name, animation name, rotation, vec<frame path>)
 
+/// Path of a file under the assets folder, as the asset server expects it.
+fn asset_path(path: &Path, assets_folder: &str) -> String {
+    let path = path.to_string_lossy().replace(assets_folder, "");
+    match path.strip_prefix('/') {
+        Some(path) => path.to_string(),
+        None => path,
+    }
+}
+
 pub fn generate_animations_ron<T: AnimationTypes>(params: AnimationGenerationParameters<T>) {
     let root_path = Path::new(&params.root_folder);
 
@@ -22,106 +32,120 @@ pub fn generate_animations_ron<T: AnimationTypes>(params: AnimationGenerationPar
         .filter_map(Result::ok)
         .filter(|e| e.path().is_dir())
     {
-        let mut animations: Vec<AnimationLoadData<T>> = Vec::new();
-        println!(
-            "processing character {}",
-            &char_entry.file_name().to_string_lossy().to_string()
-        );
-        let char_name = match char_entry.file_name().to_string_lossy().to_string() {
-            name => params.character_aliases.get(&name).cloned(),
-        };
-        let char_name = match char_name {
+        if let Err(e) = generate_character_ron(&params, &char_entry.path()) {
+            println!("Error writing RON file: {}", e);
+        }
+    }
+}
+
+/// Writes `<character>.anim.ron` next to the `character_path` folder, and the `.anim.bin` when asked for.
+/// Folders that are not a character alias are skipped.
+pub fn generate_character_ron<T: AnimationTypes>(
+    params: &AnimationGenerationParameters<T>,
+    character_path: &Path,
+) -> io::Result<()> {
+    let Some(folder_name) = character_path.file_name() else {
+        return Ok(());
+    };
+    let folder_name = folder_name.to_string_lossy().to_string();
+    let mut animations: Vec<AnimationLoadData<T>> = Vec::new();
+    println!("processing character {}", &folder_name);
+    let char_name = match params.character_aliases.get(&folder_name).cloned() {
+        Some(name) => name,
+        None => return Ok(()),
+    };
+    let metadata = params
+        .character_metadata
+        .get(&char_name)
+        .cloned()
+        .unwrap_or_default();
+
+    // Get all animation directories for this character
+    let anim_dirs = fs::read_dir(character_path)?;
+
+    // Process each animation directory
+    for anim_entry in anim_dirs
+        .filter_map(Result::ok)
+        .filter(|e| e.path().is_dir())
+    {
+        println!("processing anim {}", &folder_name);
+        let anim_name = params
+            .animation_aliases
+            .get(anim_entry.file_name().to_string_lossy().as_ref())
+            .cloned();
+        let anim_name = match anim_name {
             Some(name) => name,
             None => continue,
         };
 
-        // Get all animation directories for this character
-        let anim_dirs = match fs::read_dir(char_entry.path()) {
+        // Get all rotation directories for this animation
+        let rot_dirs = match fs::read_dir(anim_entry.path()) {
             Ok(dirs) => dirs,
             Err(_) => continue,
         };
 
-        // Process each animation directory
-        for anim_entry in anim_dirs
+        // Process each rotation directory
+        for rot_entry in rot_dirs
             .filter_map(Result::ok)
             .filter(|e| e.path().is_dir())
         {
-            println!(
-                "processing anim {}",
-                &char_entry.file_name().to_string_lossy().to_string()
-            );
-            let anim_name = match anim_entry.file_name().to_string_lossy().to_string() {
-                name => params.animation_aliases.get(&name).cloned(),
-            };
-            let anim_name = match anim_name {
+            println!("processing rotation {}", &folder_name);
+            let rot_name = params
+                .rotation_aliases
+                .get(rot_entry.file_name().to_string_lossy().as_ref())
+                .cloned();
+            let rot_name = match rot_name {
                 Some(name) => name,
                 None => continue,
             };
 
-            // Get all rotation directories for this animation
-            let rot_dirs = match fs::read_dir(anim_entry.path()) {
-                Ok(dirs) => dirs,
-                Err(_) => continue,
-            };
+            // Get and sort all PNG files
+            let mut frames: Vec<String> = fs::read_dir(rot_entry.path())
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("png"))
+                // Team colour masks belong to the frame next to them
+                .filter(|e| !e.file_name().to_string_lossy().ends_with(".mask.png"))
+                .map(|e| asset_path(&e.path(), &params.assets_folder))
+                .collect();
+
+            frames.sort();
 
-            // Process each rotation directory
-            for rot_entry in rot_dirs
-                .filter_map(Result::ok)
-                .filter(|e| e.path().is_dir())
-            {
-                println!(
-                    "processing rotation {}",
-                    &char_entry.file_name().to_string_lossy().to_string()
-                );
-                let rot_name = match rot_entry.file_name().to_string_lossy().to_string() {
-                    name => params.rotation_aliases.get(&name).cloned(),
-                };
-                let rot_name = match rot_name {
-                    Some(name) => name,
-                    None => continue,
-                };
-
-                // Get and sort all PNG files
-                let mut frames: Vec<String> = fs::read_dir(rot_entry.path())
-                    .into_iter()
-                    .flatten()
-                    .flatten()
-                    .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("png"))
-                    .map(|e| e.path().to_string_lossy().to_string())
-                    .map(|e| e.replace(params.assets_folder.as_str(), ""))
-                    .map(|e| if e.starts_with('/') { e[1..].to_string() } else { e })
-                    .collect();
-
-                frames.sort();
-
-                if !frames.is_empty() {
-                    animations.push(AnimationLoadData {
-                        character: char_name.clone(),
-                        animation: anim_name.clone(),
-                        rotation: rot_name,
-                        frames,
-                        fps: params.fps,
-                    });
-                }
+            if !frames.is_empty() {
+                animations.push(AnimationLoadData {
+                    character: char_name.clone(),
+                    animation: anim_name.clone(),
+                    rotation: rot_name,
+                    frames,
+                    fps: params.fps,
+                    metadata: metadata.clone(),
+                });
             }
         }
-        let collection = AnimationsCollection { animations };
-
-        // Serialize to RON format
-        let ron_string = ron::ser::to_string_pretty(&collection, ron::ser::PrettyConfig::default())
-            .unwrap_or_else(|e| {
-                println!("Error serializing to RON: {}", e);
-                String::new()
-            });
-
-        // Write to file
-        let ron_path = root_path.join(format!(
-            "{}.anim.ron",
-            char_entry.file_name().to_string_lossy().to_string()
-        ));
-        if let Err(e) = fs::write(ron_path, ron_string) {
-            println!("Error writing RON file: {}", e);
-        }
     }
-    // Create the final collection
+    // Clips share the fps and metadata of their character, and live in its folder
+    let base_path = asset_path(character_path, &params.assets_folder);
+    let collection = AnimationsCollection {
+        header: AnimationsHeader {
+            fps: params.fps,
+            base_path,
+            metadata,
+            ..Default::default()
+        },
+        animations,
+    };
+
+    // Serialize to RON format
+    let ron_string = ron::ser::to_string_pretty(&collection, ron::ser::PrettyConfig::default())
+        .map_err(io::Error::other)?;
+
+    // Write to file
+    let ron_path = character_path.with_file_name(format!("{}.anim.ron", folder_name));
+    fs::write(&ron_path, ron_string)?;
+    if params.binary_manifest {
+        let bytes = binary::to_binary(&collection).map_err(io::Error::other)?;
+        fs::write(ron_path.with_extension("bin"), bytes)?;
+    }
+    Ok(())
 }
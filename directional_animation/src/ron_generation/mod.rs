@@ -1,21 +1,56 @@
 pub mod animation_library;
+pub mod atlas;
+pub mod binary;
+pub mod dynamic;
+#[cfg(feature = "editor")]
+pub mod editor;
+pub mod facing;
+pub mod format;
 pub mod generate_animations_ron;
+pub mod placeholder;
 pub mod plugin;
+pub mod rotation;
+pub mod team_color;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod animator;
+pub mod trim;
+pub mod validate;
+pub mod viewer;
 
-use bevy::app::App;
-use bevy::asset::{Asset, AssetApp, AssetLoader, AsyncReadExt};
+use bevy::asset::{Asset, AssetLoader, AsyncReadExt};
 use bevy::prelude::Component;
 use bevy::reflect::{Reflect, TypePath};
-use bevy::{math::Vec3, utils::HashMap};
+use bevy::{
+    math::{Rect, Vec2, Vec3},
+    utils::HashMap,
+};
+use format::AnimationsHeader;
 use serde::{Deserialize, Serialize};
 use std::fs::{self};
 use std::hash::Hash;
 use std::path::Path;
+use team_color::TeamColorMask;
 use thiserror::Error;
 
+pub use directional_animation_derive::{AnimationAliases, AnimationTypes};
+
+/// Compact RON of a character, animation or rotation, for labels in tools.
+pub fn ron_label(value: &impl Serialize) -> String {
+    ron::to_string(value).unwrap_or_default()
+}
+
 pub trait DirectionalRotationMatcher {
     fn get_similarity(&self, movement_vector: Vec3) -> f32;
+
+    /// Movement vector that shows on screen as `screen` (`+x` right, `+y` up),
+    /// for probing rotations from screen space. Movement on the XY plane by default.
+    fn screen_to_movement(screen: Vec2) -> Vec3
+    where
+        Self: Sized,
+    {
+        screen.extend(0.0)
+    }
 }
 pub trait Converter<From, To> {
     fn convert(&self, from: From) -> Option<To>;
@@ -30,9 +65,8 @@ where
     }
 }
 
-pub trait AnimationTypes:
-    Deserialize<'static> + Serialize + Reflect + TypePath + Default + Eq + Hash
-{
+/// Can be derived, see [`AnimationTypes`](directional_animation_derive::AnimationTypes).
+pub trait AnimationTypes: TypePath + Default + Eq + Hash + Send + Sync {
     type CharacterName: Clone
         + Serialize
         + for<'a> Deserialize<'a>
@@ -59,46 +93,101 @@ pub trait AnimationTypes:
         + std::hash::Hash;
 }
 
+/// Folder names an enum is known by, used to fill the alias tables of [`AnimationGenerationParameters`].
+/// Can be derived, see [`AnimationAliases`](directional_animation_derive::AnimationAliases).
+pub trait AnimationAliases: Sized {
+    fn aliases() -> HashMap<String, Self>;
+}
+
 //TODO: utilize converters
 pub struct AnimationGenerationParameters<T: AnimationTypes> {
     pub character_aliases: HashMap<String, T::CharacterName>,
     pub animation_aliases: HashMap<String, T::AnimationName>,
     pub rotation_aliases: HashMap<String, T::Rotation>,
+    pub character_metadata: HashMap<T::CharacterName, SpriteMetadata>,
     pub root_folder: String,
     pub assets_folder: String,
     pub fps: f32,
+    /// Also write a `.anim.bin` next to every `.anim.ron`, see [`binary`].
+    pub binary_manifest: bool,
+}
+
+impl<T: AnimationTypes> AnimationGenerationParameters<T>
+where
+    T::CharacterName: AnimationAliases,
+    T::AnimationName: AnimationAliases,
+{
+    /// Character and animation aliases come from their [`AnimationAliases`] impls.
+    pub fn from_aliases(
+        root_folder: &str,
+        assets_folder: &str,
+        rotation_aliases: HashMap<String, T::Rotation>,
+        fps: f32,
+    ) -> Self {
+        Self {
+            character_aliases: T::CharacterName::aliases(),
+            animation_aliases: T::AnimationName::aliases(),
+            rotation_aliases,
+            character_metadata: HashMap::new(),
+            root_folder: root_folder.to_string(),
+            assets_folder: assets_folder.to_string(),
+            fps,
+            binary_manifest: false,
+        }
+    }
+}
+
+/// Framing information for the frames of a clip.
+/// `pivot` uses [`Anchor::Custom`](bevy::sprite::Anchor::Custom) coordinates (-0.5..0.5, y up),
+/// `hitbox` is given in source frame pixels (origin top-left, y down).
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Reflect)]
+pub struct SpriteMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot: Option<Vec2>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hitbox: Option<Rect>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_mask: Option<TeamColorMask>,
+}
+impl SpriteMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.pivot.is_none()
+            && self.scale.is_none()
+            && self.hitbox.is_none()
+            && self.team_mask.is_none()
+    }
+    /// Fills every unset field from `fallback`.
+    pub fn or(&self, fallback: &SpriteMetadata) -> SpriteMetadata {
+        SpriteMetadata {
+            pivot: self.pivot.or(fallback.pivot),
+            scale: self.scale.or(fallback.scale),
+            hitbox: self.hitbox.or(fallback.hitbox),
+            team_mask: self.team_mask.clone().or(fallback.team_mask.clone()),
+        }
+    }
 }
+
 #[derive(Serialize, Deserialize, Asset, TypePath)]
 pub struct AnimationLoadData<T: AnimationTypes> {
     pub character: T::CharacterName,
     pub animation: T::AnimationName,
     pub rotation: T::Rotation,
     pub frames: Vec<String>,
+    /// Left out in files where the clip uses the fps of the [`AnimationsHeader`].
+    #[serde(default, skip_serializing_if = "format::is_unset_fps")]
     pub fps: f32,
+    #[serde(default, skip_serializing_if = "SpriteMetadata::is_empty")]
+    pub metadata: SpriteMetadata,
 }
+/// The clips of an `.anim.ron`, with the header already applied to them.
+/// See [`format`] for how it is written to disk.
 #[derive(Asset, TypePath)]
 pub struct AnimationsCollection<T: AnimationTypes> {
+    pub header: AnimationsHeader,
     pub animations: Vec<AnimationLoadData<T>>,
 }
-impl<'de, T: AnimationTypes> Deserialize<'de> for AnimationsCollection<T> {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        Ok(AnimationsCollection {
-            animations: Vec::deserialize(deserializer)?,
-        })
-    }
-}
-impl<T: AnimationTypes> Serialize for AnimationsCollection<T> {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        // Directly serialize the animations vector as an array
-        self.animations.serialize(serializer)
-    }
-}
 
 #[derive(Default)]
 pub struct AnimationLoader<T: AnimationTypes> {
@@ -113,6 +202,19 @@ pub enum AnimationLoaderError {
     /// A [RON](ron) Error
     #[error("Could not parse RON: {0}")]
     RonSpannedError(#[from] ron::error::SpannedError),
+    /// A [bincode] Error, for `.anim.bin` manifests
+    #[error("Could not decode binary manifest: {0}")]
+    Bincode(#[from] bincode::Error),
+    #[error("Not a binary animation manifest")]
+    NotABinaryManifest,
+    #[error("Unsupported animation format version {0}")]
+    UnsupportedVersion(u32),
+}
+impl<AT: AnimationTypes> AnimationLoader<AT> {
+    /// Parses the bytes of an `.anim.ron`, as the asset loader does.
+    pub fn parse(bytes: &[u8]) -> Result<AnimationsCollection<AT>, AnimationLoaderError> {
+        Ok(ron::de::from_bytes(bytes)?)
+    }
 }
 impl<AT: AnimationTypes> AssetLoader for AnimationLoader<AT> {
     fn extensions(&self) -> &[&str] {
@@ -137,15 +239,6 @@ impl<AT: AnimationTypes> AssetLoader for AnimationLoader<AT> {
         //println!("loading animation");
         reader.read_to_end(&mut bytes).await?;
         //println!("passed read");
-        match ron::de::from_bytes(&bytes) {
-            Ok(custom_asset) => {
-                // println!("passed deserialise");
-                Ok(custom_asset)
-            }
-            Err(e) => {
-                //println!("Animation load error: {}", e);
-                Err(AnimationLoaderError::RonSpannedError(e))
-            }
-        }
+        Self::parse(&bytes)
     }
 }
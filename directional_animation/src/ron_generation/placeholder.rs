@@ -0,0 +1,207 @@
+//! Procedural placeholder frames, so characters without art can be animated while prototyping.
+//! Every frame shows an arrow pointing in the direction of its rotation,
+//! the animation name and the frame number.
+
+use bevy::{color::Hsla, prelude::*, utils::HashMap};
+use image::{Rgba, RgbaImage};
+use std::io;
+use std::path::Path;
+
+use super::{
+    generate_animations_ron::generate_character_ron, AnimationGenerationParameters, AnimationTypes,
+    DirectionalRotationMatcher,
+};
+
+pub struct PlaceholderSettings {
+    pub size: UVec2,
+    pub frames: usize,
+}
+
+impl Default for PlaceholderSettings {
+    fn default() -> Self {
+        Self {
+            size: UVec2::splat(128),
+            frames: 8,
+        }
+    }
+}
+
+/// Writes placeholder frames for every animation and rotation alias of `character_folder`
+/// into `<root_folder>/<character_folder>/<animation>/<rotation>/`, then generates its `.anim.ron`.
+/// The `.anim.ron` files of other characters are left alone.
+/// When several aliases map to the same animation only the alphabetically first one is used.
+pub fn generate_placeholder_animations<T: AnimationTypes>(
+    params: AnimationGenerationParameters<T>,
+    character_folder: &str,
+    settings: &PlaceholderSettings,
+) -> io::Result<()> {
+    if !params.character_aliases.contains_key(character_folder) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("no character alias called {}", character_folder),
+        ));
+    }
+
+    let mut animation_folders: HashMap<&T::AnimationName, &String> = HashMap::new();
+    for (alias, animation) in params.animation_aliases.iter() {
+        let folder = animation_folders.entry(animation).or_insert(alias);
+        if alias < *folder {
+            *folder = alias;
+        }
+    }
+    let animation_folders: Vec<String> = animation_folders.into_values().cloned().collect();
+
+    let character_path = Path::new(&params.root_folder).join(character_folder);
+    for animation_folder in animation_folders.iter() {
+        for (rotation_folder, rotation) in params.rotation_aliases.iter() {
+            let folder = character_path.join(animation_folder).join(rotation_folder);
+            std::fs::create_dir_all(&folder)?;
+            let direction = rotation_direction(rotation);
+            for frame in 0..settings.frames {
+                placeholder_frame(animation_folder, direction, frame, settings)
+                    .save(folder.join(format!("frame_{:04}.png", frame)))
+                    .map_err(io::Error::other)?;
+            }
+        }
+    }
+
+    generate_character_ron(&params, &character_path)
+}
+
+/// Screen space direction (x right, y up) the rotation matches best.
+pub fn rotation_direction<R: DirectionalRotationMatcher>(rotation: &R) -> Vec2 {
+    let similarity = |screen: &Vec2| rotation.get_similarity(R::screen_to_movement(*screen));
+    (0..360)
+        .map(|degrees| Vec2::from_angle((degrees as f32).to_radians()))
+        .max_by(|a, b| similarity(a).total_cmp(&similarity(b)))
+        .unwrap_or(Vec2::Y)
+}
+
+/// Draws one placeholder frame: an arrow tinted by the animation name, pointing in `direction`,
+/// with the name and `frame / frames` written in the top left corner.
+pub fn placeholder_frame(
+    animation: &str,
+    direction: Vec2,
+    frame: usize,
+    settings: &PlaceholderSettings,
+) -> RgbaImage {
+    let size = settings.size;
+    let center = size.as_vec2() / 2.0;
+    let length = size.min_element() as f32 * 0.4;
+    let direction = direction.try_normalize().unwrap_or(Vec2::Y);
+    let across = direction.perp();
+
+    // Brighten over the clip so playback is visible even when the text is too small to read
+    let progress = (frame + 1) as f32 / settings.frames.max(1) as f32;
+    let color = Color::from(Hsla::hsl(name_hue(animation), 0.8, 0.35 + 0.25 * progress))
+        .to_srgba()
+        .to_u8_array();
+
+    let mut image = RgbaImage::from_fn(size.x, size.y, |x, y| {
+        // Image rows go down, screen space goes up
+        let point = Vec2::new(x as f32 + 0.5 - center.x, center.y - y as f32 - 0.5);
+        let along = point.dot(direction);
+        let side = point.dot(across).abs();
+        let shaft = along >= -length * 0.6 && along <= length * 0.4 && side <= length * 0.12;
+        let head = along > length * 0.4 && along <= length && side <= (length - along) * 0.75;
+        if shaft || head {
+            Rgba(color)
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    });
+
+    // Tinted like the arrow, so the text reads on light and dark backgrounds
+    let scale = (size.x / 64).max(1);
+    let text = Rgba(color);
+    draw_text(
+        &mut image,
+        &animation.to_uppercase(),
+        UVec2::splat(scale),
+        scale,
+        text,
+    );
+    draw_text(
+        &mut image,
+        &format!("{}/{}", frame + 1, settings.frames),
+        UVec2::new(scale, scale * 7),
+        scale,
+        text,
+    );
+    image
+}
+
+fn name_hue(name: &str) -> f32 {
+    let hash = name.bytes().fold(2166136261u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(16777619)
+    });
+    (hash % 360) as f32
+}
+
+/// Writes `text` with a 3x5 pixel font, characters that do not fit are cut off.
+fn draw_text(image: &mut RgbaImage, text: &str, position: UVec2, scale: u32, color: Rgba<u8>) {
+    for (index, character) in text.chars().enumerate() {
+        let left = position.x + index as u32 * 4 * scale;
+        for (row, bits) in glyph(character).iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let x = left + column * scale + dx;
+                        let y = position.y + row as u32 * scale + dy;
+                        if x < image.width() && y < image.height() {
+                            image.put_pixel(x, y, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn glyph(character: char) -> [u8; 5] {
+    match character.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '_' => [0b000, 0b000, 0b000, 0b000, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        _ => [0; 5],
+    }
+}
@@ -1,12 +1,16 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, render::renderer::RenderDevice, sprite::SpritePlugin};
 use std::marker::PhantomData;
 
 use super::{
     animation_library::{
-        self, are_all_animation_sprites_loaded, load_sprites, AnimationLibrary,
-        AnimationWithPathsToHandles, AnimationsWithPaths,
+        are_all_animation_sprites_loaded, load_sprites, AnimationLibrary,
+        AnimationWithPathsToHandles, AnimationsWithPaths, MyAnimationClip,
     },
-    animator::{animate, change_animation},
+    animator::{animate, change_animation, AnimationTimer, FallbackAnimation, MovementDirection},
+    atlas::{AnimationAtlasSettings, AtlasSettings},
+    binary::BinaryAnimationLoader,
+    facing::{resolve_facing, Facing, FacingSettings, FacingTarget},
+    team_color::{TeamColor, TeamColorPlugin, TeamColorSprite},
     AnimationLoader, AnimationTypes, AnimationsCollection,
 };
 
@@ -83,13 +87,20 @@ pub fn check_sprites_loaded<T: AnimationTypes>(
 pub fn build_animation_library<T: AnimationTypes>(
     mut next_state: ResMut<NextState<AnimationLoadingState>>,
     animations_with_handles: Res<AnimationWithPathsToHandles<T>>,
+    mut atlas_settings: ResMut<AnimationAtlasSettings<T>>,
+    render_device: Option<Res<RenderDevice>>,
     textures: ResMut<Assets<Image>>,
     texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     mut animation_library: ResMut<AnimationLibrary<T>>,
 ) {
-    animation_library.animations = animations_with_handles
-        .build_animation_library(textures, texture_atlas_layouts)
-        .animations;
+    if let Some(render_device) = render_device {
+        atlas_settings.texture_size_limit = Some(render_device.limits().max_texture_dimension_2d);
+    }
+    *animation_library = animations_with_handles.build_animation_library(
+        &atlas_settings,
+        textures,
+        texture_atlas_layouts,
+    );
     next_state.set(AnimationLoadingState::Complete);
 }
 
@@ -97,6 +108,7 @@ pub fn build_animation_library<T: AnimationTypes>(
 pub struct LoadAnimationPlugin<T: AnimationTypes> {
     phantom: PhantomData<T>,
     paths: Option<Vec<String>>,
+    atlas_settings: AnimationAtlasSettings<T>,
 }
 
 impl<T: AnimationTypes> LoadAnimationPlugin<T> {
@@ -104,14 +116,31 @@ impl<T: AnimationTypes> LoadAnimationPlugin<T> {
         Self {
             phantom: PhantomData,
             paths: Some(paths),
+            atlas_settings: AnimationAtlasSettings::default(),
         }
     }
+    /// Atlas settings used for every character without its own settings.
+    pub fn with_atlas_settings(mut self, settings: AtlasSettings) -> Self {
+        self.atlas_settings.default = settings;
+        self
+    }
+    pub fn with_character_atlas_settings(
+        mut self,
+        character: T::CharacterName,
+        settings: AtlasSettings,
+    ) -> Self {
+        self.atlas_settings
+            .per_character
+            .insert(character, settings);
+        self
+    }
 }
 
 impl<T: AnimationTypes> Plugin for LoadAnimationPlugin<T> {
     fn build(&self, app: &mut App) {
         app.init_asset::<AnimationsCollection<T>>();
         app.init_asset_loader::<AnimationLoader<T>>();
+        app.init_asset_loader::<BinaryAnimationLoader<T>>();
         app.insert_state(AnimationLoadingState::LoadingAnimFiles);
         app.insert_resource(if let Some(ref paths) = self.paths {
             AnimationPaths {
@@ -121,6 +150,7 @@ impl<T: AnimationTypes> Plugin for LoadAnimationPlugin<T> {
             AnimationPaths::default()
         });
 
+        app.insert_resource(self.atlas_settings.clone());
         app.init_resource::<AnimationWithPathsToHandles<T>>();
         app.init_resource::<AnimationLibrary<T>>();
         app.add_systems(
@@ -148,25 +178,61 @@ impl<T: AnimationTypes> Plugin for LoadAnimationPlugin<T> {
 
 pub struct AnimatePlugin<T: AnimationTypes> {
     phantom: PhantomData<T>,
+    facing: FacingSettings,
+    fallback: Option<T::AnimationName>,
 }
 
 impl<T: AnimationTypes> Default for AnimatePlugin<T> {
     fn default() -> Self {
         Self {
             phantom: Default::default(),
+            facing: FacingSettings::default(),
+            fallback: None,
         }
     }
 }
 
+impl<T: AnimationTypes> AnimatePlugin<T> {
+    /// Match rotations against facing as seen through the camera, for isometric or 3D cameras.
+    pub fn with_camera_facing(mut self) -> Self {
+        self.facing.project_through_camera = true;
+        self
+    }
+
+    /// Show the first frame of `animation` for characters missing the requested one,
+    /// see [`FallbackAnimation`].
+    pub fn with_fallback_animation(mut self, animation: T::AnimationName) -> Self {
+        self.fallback = Some(animation);
+        self
+    }
+}
+
 impl<T: AnimationTypes> Plugin for AnimatePlugin<T> {
     fn build(&self, app: &mut App) {
+        app.register_type::<MyAnimationClip>()
+            .register_type::<AnimationTimer>()
+            .register_type::<MovementDirection>()
+            .register_type::<Facing>()
+            .register_type::<FacingTarget>()
+            .register_type::<TeamColor>()
+            .register_type::<TeamColorSprite>();
+        app.insert_resource(self.facing.clone());
+        if let Some(fallback) = &self.fallback {
+            app.insert_resource(FallbackAnimation::<T>(fallback.clone()));
+        }
         app.add_systems(
             Update,
-            change_animation::<T>.run_if(in_state(AnimationLoadingState::Complete)),
+            (resolve_facing, change_animation::<T>)
+                .chain()
+                .run_if(in_state(AnimationLoadingState::Complete)),
         );
         app.add_systems(
             Update,
             animate.run_if(in_state(AnimationLoadingState::Complete)),
         );
+        // Headless apps have nothing to draw team colours with
+        if app.is_plugin_added::<SpritePlugin>() {
+            app.add_plugins(TeamColorPlugin);
+        }
     }
 }
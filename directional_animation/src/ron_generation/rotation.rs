@@ -0,0 +1,325 @@
+//! Ready made [`DirectionalRotationMatcher`]s, so games only have to describe how their
+//! rotation folders are numbered instead of implementing the angle maths themselves.
+
+use bevy::{math::EulerRot, prelude::*, utils::HashMap};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use super::DirectionalRotationMatcher;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
+/// Plane the movement vector is read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementPlane {
+    /// 2D games, `+y` is up.
+    XY,
+    /// 3D ground plane, `-z` (bevy's forward) is up.
+    XZ,
+}
+
+/// How movement on the plane ends up on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    TopDown,
+    /// Classic 2:1 isometric, the plane's `+x` axis points to the lower right of the screen
+    /// and `+y` to the upper right.
+    Isometric,
+}
+
+/// Describes how the degrees of an [`AngleRotation`] are measured.
+/// Angles are measured on screen after the [`Projection`] is applied.
+pub trait AngleConvention: Send + Sync + 'static {
+    const PLANE: MovementPlane = MovementPlane::XY;
+    const PROJECTION: Projection = Projection::TopDown;
+    /// Screen angle of the 0 degree rotation, counter-clockwise from the right.
+    const ZERO_ANGLE: f32 = 0.0;
+    const WINDING: Winding = Winding::CounterClockwise;
+}
+
+/// 0 degrees points right, angles grow counter-clockwise.
+pub struct MathAngles;
+impl AngleConvention for MathAngles {}
+
+/// 0 degrees points up, angles grow clockwise like a compass.
+pub struct CompassAngles;
+impl AngleConvention for CompassAngles {
+    const ZERO_ANGLE: f32 = 90.0;
+    const WINDING: Winding = Winding::Clockwise;
+}
+
+/// Compass angles on a 2:1 isometric screen.
+pub struct IsometricCompassAngles;
+impl AngleConvention for IsometricCompassAngles {
+    const PROJECTION: Projection = Projection::Isometric;
+    const ZERO_ANGLE: f32 = 90.0;
+    const WINDING: Winding = Winding::Clockwise;
+}
+
+/// Screen direction of a movement vector according to the convention.
+pub fn screen_direction<C: AngleConvention>(movement: Vec3) -> Vec2 {
+    let planar = match C::PLANE {
+        MovementPlane::XY => movement.xy(),
+        MovementPlane::XZ => Vec2::new(movement.x, -movement.z),
+    };
+    match C::PROJECTION {
+        Projection::TopDown => planar,
+        Projection::Isometric => Vec2::new(planar.x + planar.y, (planar.y - planar.x) * 0.5),
+    }
+}
+
+/// Movement vector showing on screen as `screen`, the inverse of [`screen_direction`].
+pub fn movement_for_screen<C: AngleConvention>(screen: Vec2) -> Vec3 {
+    let planar = match C::PROJECTION {
+        Projection::TopDown => screen,
+        Projection::Isometric => {
+            Vec2::new(screen.x - 2.0 * screen.y, screen.x + 2.0 * screen.y) * 0.5
+        }
+    };
+    match C::PLANE {
+        MovementPlane::XY => planar.extend(0.0),
+        MovementPlane::XZ => Vec3::new(planar.x, 0.0, -planar.y),
+    }
+}
+
+/// Angle of a screen direction in degrees of the convention, in `0..360`.
+pub fn convention_degrees<C: AngleConvention>(screen: Vec2) -> f32 {
+    screen_degrees(screen, C::ZERO_ANGLE, C::WINDING)
+}
+
+/// Angle of a screen direction in degrees from `zero_angle`, in `0..360`,
+/// for conventions only known at runtime.
+pub fn screen_degrees(screen: Vec2, zero_angle: f32, winding: Winding) -> f32 {
+    let angle = screen.y.atan2(screen.x).to_degrees();
+    let degrees = match winding {
+        Winding::CounterClockwise => angle - zero_angle,
+        Winding::Clockwise => zero_angle - angle,
+    };
+    degrees.rem_euclid(360.0)
+}
+
+/// A rotation drawn at a whole number of degrees, see [`AngleConvention`] for how they are measured.
+/// Serializes as `(degrees)`.
+pub struct AngleRotation<C: AngleConvention> {
+    pub degrees: u32,
+    phantom: PhantomData<C>,
+}
+
+impl<C: AngleConvention> AngleRotation<C> {
+    pub fn new(degrees: u32) -> Self {
+        Self {
+            degrees: degrees % 360,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The `index`th of `count` evenly spaced rotations, starting at the zero angle.
+    pub fn sector(index: u32, count: u32) -> Self {
+        Self::new((index as f32 * 360.0 / count.max(1) as f32).round() as u32)
+    }
+
+    pub fn sectors(count: u32) -> Vec<Self> {
+        (0..count).map(|index| Self::sector(index, count)).collect()
+    }
+
+    /// Aliases for `count` evenly spaced rotations, named by their degrees (`"0"`, `"45"`, ...).
+    pub fn sector_aliases(count: u32) -> HashMap<String, Self> {
+        Self::sectors(count)
+            .into_iter()
+            .map(|rotation| (rotation.degrees.to_string(), rotation))
+            .collect()
+    }
+
+    /// Rotation closest to a screen direction.
+    pub fn from_screen_direction(screen: Vec2) -> Self {
+        Self::new(convention_degrees::<C>(screen).round() as u32)
+    }
+
+    /// Reads folder names like `45`, `rot_045`, `NE` or `north_east`.
+    /// Numbers are degrees, compass names are screen directions.
+    pub fn from_folder_name(name: &str) -> Option<Self> {
+        if let Some(direction) = compass_direction(name) {
+            return Some(Self::from_screen_direction(direction));
+        }
+        trailing_number(name).map(Self::new)
+    }
+}
+
+impl<C: AngleConvention> DirectionalRotationMatcher for AngleRotation<C> {
+    fn get_similarity(&self, movement_vector: Vec3) -> f32 {
+        let movement_degrees = convention_degrees::<C>(screen_direction::<C>(movement_vector));
+
+        // Shortest angle difference, normalized so 1.0 is a perfect match
+        let mut difference = (self.degrees as f32 - movement_degrees).abs();
+        if difference > 180.0 {
+            difference = 360.0 - difference;
+        }
+        1.0 - (difference / 180.0)
+    }
+
+    fn screen_to_movement(screen: Vec2) -> Vec3 {
+        movement_for_screen::<C>(screen)
+    }
+}
+
+// Manual impls, deriving would require the convention marker to implement them too
+impl<C: AngleConvention> Clone for AngleRotation<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<C: AngleConvention> Copy for AngleRotation<C> {}
+impl<C: AngleConvention> PartialEq for AngleRotation<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.degrees == other.degrees
+    }
+}
+impl<C: AngleConvention> Eq for AngleRotation<C> {}
+impl<C: AngleConvention> Hash for AngleRotation<C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.degrees.hash(state);
+    }
+}
+impl<C: AngleConvention> fmt::Debug for AngleRotation<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AngleRotation({})", self.degrees)
+    }
+}
+impl<C: AngleConvention> Serialize for AngleRotation<C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct("AngleRotation", &self.degrees)
+    }
+}
+impl<'de, C: AngleConvention> Deserialize<'de> for AngleRotation<C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Degrees(u32);
+        Degrees::deserialize(deserializer).map(|degrees| Self::new(degrees.0))
+    }
+}
+
+/// A rotation of a 3D model rendered from several heights, angles in whole degrees.
+/// Yaw turns counter-clockwise seen from above starting at `-z`, positive pitch looks up.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct YawPitchRotation {
+    pub yaw: i32,
+    pub pitch: i32,
+}
+
+impl YawPitchRotation {
+    pub fn direction(&self) -> Vec3 {
+        Quat::from_euler(
+            EulerRot::YXZ,
+            (self.yaw as f32).to_radians(),
+            (self.pitch as f32).to_radians(),
+            0.0,
+        ) * Vec3::NEG_Z
+    }
+
+    /// Every combination of `yaw_count` evenly spaced yaws with the given pitches.
+    pub fn grid(yaw_count: u32, pitches: &[i32]) -> Vec<Self> {
+        pitches
+            .iter()
+            .flat_map(|&pitch| {
+                (0..yaw_count).map(move |index| Self {
+                    yaw: (index as f32 * 360.0 / yaw_count.max(1) as f32).round() as i32,
+                    pitch,
+                })
+            })
+            .collect()
+    }
+
+    /// Reads folder names like `45_-30` as `yaw_pitch`, a single number is a yaw at pitch 0.
+    pub fn from_folder_name(name: &str) -> Option<Self> {
+        match name.split_once('_') {
+            Some((yaw, pitch)) => Some(Self {
+                yaw: yaw.parse().ok()?,
+                pitch: pitch.parse().ok()?,
+            }),
+            None => Some(Self {
+                yaw: name.parse().ok()?,
+                pitch: 0,
+            }),
+        }
+    }
+}
+
+impl DirectionalRotationMatcher for YawPitchRotation {
+    fn get_similarity(&self, movement_vector: Vec3) -> f32 {
+        let Some(movement) = movement_vector.try_normalize() else {
+            return 0.0;
+        };
+        // Cosine of the angle in between, mapped to 0..1
+        (self.direction().dot(movement) + 1.0) * 0.5
+    }
+
+    /// Seen from above, screen up is bevy's forward.
+    fn screen_to_movement(screen: Vec2) -> Vec3 {
+        Vec3::new(screen.x, 0.0, -screen.y)
+    }
+}
+
+/// Screen direction of compass folder names such as `N`, `se` or `south_west`.
+pub fn compass_direction(name: &str) -> Option<Vec2> {
+    let name = name.to_lowercase().replace(['_', '-', ' '], "");
+    let (x, y) = match name.as_str() {
+        "n" | "north" | "up" => (0.0, 1.0),
+        "ne" | "northeast" | "upright" => (1.0, 1.0),
+        "e" | "east" | "right" => (1.0, 0.0),
+        "se" | "southeast" | "downright" => (1.0, -1.0),
+        "s" | "south" | "down" => (0.0, -1.0),
+        "sw" | "southwest" | "downleft" => (-1.0, -1.0),
+        "w" | "west" | "left" => (-1.0, 0.0),
+        "nw" | "northwest" | "upleft" => (-1.0, 1.0),
+        _ => return None,
+    };
+    Some(Vec2::new(x, y))
+}
+
+fn trailing_number(name: &str) -> Option<u32> {
+    let digits = name.len() - name.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    name[name.len() - digits..].parse().ok()
+}
+
+/// Collects every rotation folder under `root` (`<character>/<animation>/<rotation>`)
+/// that `parse` understands.
+pub fn rotation_aliases_from_folders<R>(
+    root: &Path,
+    parse: impl Fn(&str) -> Option<R>,
+) -> HashMap<String, R> {
+    let mut aliases = HashMap::new();
+    let subfolders = |path: &Path| {
+        std::fs::read_dir(path)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>()
+    };
+    for character in subfolders(root) {
+        for animation in subfolders(&character) {
+            for rotation in subfolders(&animation) {
+                let name = rotation
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                if aliases.contains_key(&name) {
+                    continue;
+                }
+                if let Some(parsed) = parse(&name) {
+                    aliases.insert(name, parsed);
+                }
+            }
+        }
+    }
+    aliases
+}
@@ -0,0 +1,383 @@
+use bevy::{
+    asset::load_internal_asset,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        primitives::Aabb,
+        render_asset::RenderAssetUsages,
+        render_resource::{AsBindGroup, ShaderRef, TextureFormat},
+    },
+    sprite::{Material2d, Material2dPlugin, Mesh2dHandle},
+    utils::{HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    animation_library::MyAnimationClip,
+    animator::{animate, AnimationTimer},
+    plugin::AnimationLoadingState,
+    trim::crop_image,
+};
+
+/// Where the team colour mask of a clip comes from.
+/// Masks are packed into their own atlas with the same layout as the frames,
+/// so every team shares the same textures.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Reflect)]
+pub enum TeamColorMask {
+    /// Every frame has a `<frame>.mask.png` next to it.
+    /// The mask colour is multiplied with the team colour, its alpha decides coverage.
+    MaskFrames,
+    /// Pixels whose hue is within `tolerance` degrees of `hue` are team coloured,
+    /// keeping their brightness as shading.
+    KeyColor {
+        hue: f32,
+        tolerance: f32,
+        min_saturation: f32,
+    },
+}
+
+impl TeamColorMask {
+    /// Path of the mask frame belonging to `frame` for the [`TeamColorMask::MaskFrames`] convention.
+    pub fn mask_path(frame: &str) -> String {
+        match frame.strip_suffix(".png") {
+            Some(stem) => format!("{}.mask.png", stem),
+            None => format!("{}.mask", frame),
+        }
+    }
+}
+
+/// Builds a mask from the pixels of `image` that fall into the key colour range.
+pub fn key_color_mask(image: &Image, hue: f32, tolerance: f32, min_saturation: f32) -> Image {
+    let mut mask = image.clone();
+    let bgra = matches!(
+        image.texture_descriptor.format,
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb
+    );
+    for pixel in mask.data.chunks_exact_mut(4) {
+        let (r, g, b) = if bgra {
+            (pixel[2], pixel[1], pixel[0])
+        } else {
+            (pixel[0], pixel[1], pixel[2])
+        };
+        let hsva = Hsva::from(Color::srgb_u8(r, g, b));
+        let mut difference = (hsva.hue - hue).abs() % 360.0;
+        if difference > 180.0 {
+            difference = 360.0 - difference;
+        }
+        if difference <= tolerance && hsva.saturation >= min_saturation {
+            let shade = (hsva.value * 255.0) as u8;
+            pixel[..3].copy_from_slice(&[shade, shade, shade]);
+        } else {
+            pixel.copy_from_slice(&[0, 0, 0, 0]);
+        }
+    }
+    mask
+}
+
+/// Crops a full size mask frame to the region its frame was trimmed to.
+pub fn crop_mask(mask: &Image, frame_size: UVec2, rect: URect) -> Option<Image> {
+    if mask.size() != frame_size {
+        warn!(
+            "Team colour mask of size {} does not match its frame of size {}",
+            mask.size(),
+            frame_size
+        );
+        return None;
+    }
+    if rect == URect::from_corners(UVec2::ZERO, frame_size) {
+        return Some(mask.clone());
+    }
+    Some(crop_image(mask, rect))
+}
+
+pub const TEAM_COLOR_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(13817216397548219347);
+
+/// Recolours the team colour mask of an animated sprite.
+/// Entities with a team colour are drawn as a mesh with a [`TeamColorMaterial`] instead of their
+/// `Sprite`, which is moved into a [`TeamColorSprite`] while the team colour is set.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct TeamColor(pub Color);
+
+/// The `Sprite` of an entity with a [`TeamColor`].
+/// Its colour, flips, size and anchor are applied to the mesh, and it is put back
+/// when the team colour is removed. Inserting a new `Sprite` replaces it.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct TeamColorSprite(pub Sprite);
+
+/// Draws an atlas page with its team colour mask multiplied by `color`.
+/// Shared by every entity showing the same page in the same colour.
+#[derive(Asset, AsBindGroup, TypePath, Debug, Clone)]
+pub struct TeamColorMaterial {
+    /// Transparent for pages without a mask, which are drawn as they are.
+    #[uniform(0)]
+    pub color: LinearRgba,
+    /// Multiplied with the result like `Sprite::color`.
+    #[uniform(5)]
+    pub tint: LinearRgba,
+    #[texture(1)]
+    #[sampler(2)]
+    pub texture: Handle<Image>,
+    #[texture(3)]
+    #[sampler(4)]
+    pub mask: Option<Handle<Image>>,
+}
+
+impl Material2d for TeamColorMaterial {
+    fn fragment_shader() -> ShaderRef {
+        TEAM_COLOR_SHADER_HANDLE.into()
+    }
+}
+
+/// Atlas entry, anchor and size bits and flips of a frame quad.
+type FrameMeshKey = (AssetId<TextureAtlasLayout>, usize, [u32; 4], [bool; 2]);
+/// Atlas page, team colour bits and tint bits of a material.
+type PageMaterialKey = (AssetId<Image>, [u32; 4], [u32; 4]);
+
+/// Quads and materials of every frame and team colour in use, so units share them.
+/// Only ids are kept, so an asset is dropped once no entity draws with it.
+#[derive(Resource, Default)]
+pub struct TeamColorAssets {
+    meshes: HashMap<FrameMeshKey, AssetId<Mesh>>,
+    materials: HashMap<PageMaterialKey, AssetId<TeamColorMaterial>>,
+}
+
+pub struct TeamColorPlugin;
+
+/// This plugin draws entities with a [`TeamColor`] through the [`TeamColorMaterial`]
+/// It needs the sprite renderer, so `AnimatePlugin` only adds it after the `SpritePlugin`
+impl Plugin for TeamColorPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            TEAM_COLOR_SHADER_HANDLE,
+            "team_color.wgsl",
+            Shader::from_wgsl
+        );
+        app.add_plugins(Material2dPlugin::<TeamColorMaterial>::default())
+            .init_resource::<TeamColorAssets>()
+            .add_systems(
+                Update,
+                (
+                    update_team_color_meshes.after(animate),
+                    remove_team_color_meshes,
+                )
+                    .run_if(in_state(AnimationLoadingState::Complete)),
+            )
+            .add_systems(Last, prune_team_color_assets);
+    }
+}
+
+/// A quad like a sprite with this `anchor`, `size` and `flip` would draw, showing entry `index` of `layout`.
+fn frame_quad(
+    anchor: Vec2,
+    size: Vec2,
+    [flip_x, flip_y]: [bool; 2],
+    layout: &TextureAtlasLayout,
+    index: usize,
+) -> Mesh {
+    let rect = layout.textures[index].as_rect();
+    let mut uv_min = rect.min / layout.size.as_vec2();
+    let mut uv_max = rect.max / layout.size.as_vec2();
+    if flip_x {
+        std::mem::swap(&mut uv_min.x, &mut uv_max.x);
+    }
+    if flip_y {
+        std::mem::swap(&mut uv_min.y, &mut uv_max.y);
+    }
+    let corner = |x: f32, y: f32| [(x - anchor.x) * size.x, (y - anchor.y) * size.y, 0.0];
+    // Kept in the main world so `TeamColorAssets` can hand out new handles to it
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        vec![
+            corner(0.5, 0.5),
+            corner(-0.5, 0.5),
+            corner(-0.5, -0.5),
+            corner(0.5, -0.5),
+        ],
+    )
+    .with_inserted_attribute(
+        Mesh::ATTRIBUTE_UV_0,
+        vec![
+            [uv_max.x, uv_min.y],
+            [uv_min.x, uv_min.y],
+            [uv_min.x, uv_max.y],
+            [uv_max.x, uv_max.y],
+        ],
+    )
+    .with_inserted_indices(Indices::U16(vec![0, 1, 2, 0, 2, 3]))
+}
+
+pub fn update_team_color_meshes(
+    mut commands: Commands,
+    mut team_color_assets: ResMut<TeamColorAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<TeamColorMaterial>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    query: Query<(
+        Entity,
+        &MyAnimationClip,
+        &AnimationTimer,
+        &TeamColor,
+        Option<&Sprite>,
+        Option<&TeamColorSprite>,
+        Option<&Mesh2dHandle>,
+        Option<&Handle<TeamColorMaterial>>,
+    )>,
+) {
+    let TeamColorAssets {
+        meshes: frame_meshes,
+        materials: page_materials,
+    } = &mut *team_color_assets;
+    for (entity, clip, timer, team_color, sprite, team_color_sprite, mesh, material) in query.iter()
+    {
+        let frame = timer.current_frame % clip.len;
+        let location = clip.frame_locations[frame];
+        let page = &clip.pages[location.page];
+        let Some(layout) = layouts.get(&page.texture_atlas_layout_handle) else {
+            continue;
+        };
+        // Drawn like the sprite would be after the animator applied this frame to it
+        let mut frame_sprite = sprite
+            .or(team_color_sprite.map(|team_color_sprite| &team_color_sprite.0))
+            .cloned()
+            .unwrap_or_default();
+        clip.apply_frame(frame, &mut frame_sprite);
+        let anchor = frame_sprite.anchor.as_vec();
+        let size = frame_sprite
+            .custom_size
+            .unwrap_or_else(|| layout.textures[location.index].size().as_vec2());
+        let flip = [frame_sprite.flip_x, frame_sprite.flip_y];
+        let mesh_key = (
+            page.texture_atlas_layout_handle.id(),
+            location.index,
+            [anchor.x, anchor.y, size.x, size.y].map(f32::to_bits),
+            flip,
+        );
+        let frame_mesh = match frame_meshes
+            .get(&mesh_key)
+            .and_then(|id| meshes.get_strong_handle(*id))
+        {
+            Some(handle) => handle,
+            None => {
+                let handle = meshes.add(frame_quad(anchor, size, flip, layout, location.index));
+                frame_meshes.insert(mesh_key, handle.id());
+                handle
+            }
+        };
+        let frame_mesh = Mesh2dHandle(frame_mesh);
+
+        let color = LinearRgba::from(team_color.0);
+        let tint = LinearRgba::from(frame_sprite.color);
+        let material_key = (
+            page.texture_atlas.id(),
+            color.to_f32_array().map(f32::to_bits),
+            tint.to_f32_array().map(f32::to_bits),
+        );
+        let page_material = match page_materials
+            .get(&material_key)
+            .and_then(|id| materials.get_strong_handle(*id))
+        {
+            Some(handle) => handle,
+            None => {
+                let handle = materials.add(TeamColorMaterial {
+                    color: if page.team_mask.is_some() {
+                        color
+                    } else {
+                        LinearRgba::NONE
+                    },
+                    tint,
+                    texture: page.texture_atlas.clone(),
+                    mask: page.team_mask.clone(),
+                });
+                page_materials.insert(material_key, handle.id());
+                handle
+            }
+        };
+
+        let mut entity = commands.entity(entity);
+        if mesh != Some(&frame_mesh) {
+            // Culled like the sprite it replaces
+            entity.insert((
+                Aabb {
+                    center: (-anchor * size).extend(0.0).into(),
+                    half_extents: (0.5 * size).extend(0.0).into(),
+                },
+                frame_mesh,
+            ));
+        }
+        if material != Some(&page_material) {
+            entity.insert(page_material);
+        }
+        if let Some(sprite) = sprite {
+            entity
+                .insert(TeamColorSprite(sprite.clone()))
+                .remove::<Sprite>();
+        }
+    }
+}
+
+/// Forgets the quads and materials no entity draws with anymore.
+pub fn prune_team_color_assets(
+    mut team_color_assets: ResMut<TeamColorAssets>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    mut material_events: EventReader<AssetEvent<TeamColorMaterial>>,
+) {
+    let removed_meshes: HashSet<_> = mesh_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Removed { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let removed_materials: HashSet<_> = material_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Removed { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if !removed_meshes.is_empty() {
+        team_color_assets
+            .meshes
+            .retain(|_, id| !removed_meshes.contains(id));
+    }
+    if !removed_materials.is_empty() {
+        team_color_assets
+            .materials
+            .retain(|_, id| !removed_materials.contains(id));
+    }
+}
+
+/// Draws an entity as a sprite again when it loses its [`TeamColor`].
+pub fn remove_team_color_meshes(
+    mut commands: Commands,
+    mut removed: RemovedComponents<TeamColor>,
+    clips: Query<(&MyAnimationClip, &AnimationTimer), With<Handle<TeamColorMaterial>>>,
+    team_color_sprites: Query<&TeamColorSprite>,
+) {
+    for entity in removed.read() {
+        let Ok((clip, timer)) = clips.get(entity) else {
+            continue;
+        };
+        let mut sprite = team_color_sprites
+            .get(entity)
+            .map(|team_color_sprite| team_color_sprite.0.clone())
+            .unwrap_or_default();
+        clip.apply_frame(timer.current_frame % clip.len, &mut sprite);
+        commands
+            .entity(entity)
+            .remove::<(
+                Mesh2dHandle,
+                Handle<TeamColorMaterial>,
+                Aabb,
+                TeamColorSprite,
+            )>()
+            .insert(sprite);
+    }
+}
@@ -0,0 +1,35 @@
+#import bevy_sprite::{
+    mesh2d_vertex_output::VertexOutput,
+    mesh2d_view_bindings::view,
+}
+
+#ifdef TONEMAP_IN_SHADER
+#import bevy_core_pipeline::tonemapping
+#endif
+
+@group(2) @binding(0) var<uniform> team_color: vec4<f32>;
+@group(2) @binding(1) var texture: texture_2d<f32>;
+@group(2) @binding(2) var texture_sampler: sampler;
+@group(2) @binding(3) var mask: texture_2d<f32>;
+@group(2) @binding(4) var mask_sampler: sampler;
+@group(2) @binding(5) var<uniform> tint: vec4<f32>;
+
+@fragment
+fn fragment(
+    mesh: VertexOutput,
+) -> @location(0) vec4<f32> {
+    var output_color = textureSample(texture, texture_sampler, mesh.uv);
+    let mask_color = textureSample(mask, mask_sampler, mesh.uv);
+    // The mask shades the team colour, its alpha decides how much of the frame is recoloured
+    let coverage = mask_color.a * team_color.a;
+    output_color = vec4<f32>(
+        mix(output_color.rgb, mask_color.rgb * team_color.rgb, coverage),
+        output_color.a
+    );
+    // Like the vertex colour of a sprite
+    output_color = output_color * tint;
+#ifdef TONEMAP_IN_SHADER
+    output_color = tonemapping::tone_mapping(output_color, view.color_grading);
+#endif
+    return output_color;
+}
@@ -0,0 +1,219 @@
+//! Headless helpers for testing the animation pipeline without a window or GPU.
+//! Frames are generated into a temporary assets folder, loaded through the regular
+//! [`LoadAnimationPlugin`] and animated with a fixed time step.
+
+use bevy::{
+    asset::{
+        handle_internal_asset_events, LoadState, RecursiveDependencyLoadState, UntypedAssetId,
+    },
+    prelude::*,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::{
+    animation_library::{AnimationLibrary, AnimationWithPathsToHandles, MyAnimationClip},
+    animator::{AnimationTimer, MovementDirection},
+    plugin::{AnimatePlugin, AnimationLoadingState, LoadAnimationPlugin},
+    AnimationTypes,
+};
+
+/// Creates an empty folder under the system temp dir, unique per test name and process.
+pub fn temp_assets_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "directional_animation_{}_{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("could not create temporary assets folder");
+    dir
+}
+
+/// Folder names of a generated clip, laid out like `generate_animations_ron` expects:
+/// `<character>/<animation>/<rotation>/<frame>.png`.
+pub struct SyntheticClip<'a> {
+    pub character: &'a str,
+    pub animation: &'a str,
+    pub rotations: &'a [&'a str],
+    pub frames: usize,
+    pub size: UVec2,
+}
+
+/// Writes a square per frame, surrounded by a transparent border and tinted by the frame index.
+pub fn write_synthetic_clip(root: &Path, clip: &SyntheticClip) -> std::io::Result<()> {
+    for rotation in clip.rotations {
+        let folder = root
+            .join(clip.character)
+            .join(clip.animation)
+            .join(rotation);
+        std::fs::create_dir_all(&folder)?;
+        for frame in 0..clip.frames {
+            let shade = (255 * (frame + 1) / clip.frames.max(1)) as u8;
+            let border = clip.size / 4;
+            let image = image::RgbaImage::from_fn(clip.size.x, clip.size.y, |x, y| {
+                let inside = x >= border.x
+                    && y >= border.y
+                    && x < clip.size.x - border.x
+                    && y < clip.size.y - border.y;
+                if inside {
+                    image::Rgba([shade, 255 - shade, 128, 255])
+                } else {
+                    image::Rgba([0, 0, 0, 0])
+                }
+            });
+            image
+                .save(folder.join(format!("frame_{:04}.png", frame)))
+                .map_err(std::io::Error::other)?;
+        }
+    }
+    Ok(())
+}
+
+/// Longest wait for the files of one loading step before a test gives up.
+const LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An `App` with just enough plugins to load and play animations.
+pub struct AnimationTestApp<T: AnimationTypes> {
+    pub app: App,
+    phantom: PhantomData<T>,
+}
+
+impl<T: AnimationTypes> AnimationTestApp<T> {
+    /// `frame_time` is how far the clock advances on every update.
+    pub fn new(assets_dir: &Path, collections: Vec<String>, frame_time: Duration) -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                file_path: assets_dir.to_string_lossy().to_string(),
+                ..default()
+            },
+            StatesPlugin,
+            ImagePlugin::default(),
+        ));
+        app.init_asset::<TextureAtlasLayout>();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(frame_time));
+        app.add_plugins((
+            LoadAnimationPlugin::<T>::new(collections),
+            AnimatePlugin::<T>::default(),
+        ));
+        // `App::run` would do this for us
+        app.finish();
+        app.cleanup();
+        Self {
+            app,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn loading_state(&self) -> AnimationLoadingState {
+        *self
+            .app
+            .world()
+            .resource::<State<AnimationLoadingState>>()
+            .get()
+    }
+
+    /// Updates until the library is built, panicking after `max_updates`.
+    /// Every loading step takes the same number of updates, however fast the files are read.
+    pub fn load(&mut self, max_updates: usize) -> &AnimationLibrary<T> {
+        for _ in 0..max_updates {
+            self.app.update();
+            if self.loading_state() == AnimationLoadingState::Complete {
+                return self.library();
+            }
+            self.wait_for_loads();
+        }
+        panic!(
+            "animations did not load within {} updates, stuck in {:?}",
+            max_updates,
+            self.loading_state()
+        );
+    }
+
+    /// Blocks until the IO task pool has read every animation file and frame requested so far,
+    /// and hands them to the asset server like the next update would.
+    pub fn wait_for_loads(&mut self) {
+        let deadline = Instant::now() + LOAD_TIMEOUT;
+        loop {
+            handle_internal_asset_events(self.app.world_mut());
+            if !self.loads_in_flight() {
+                return;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "assets did not load within {:?}",
+                LOAD_TIMEOUT
+            );
+            std::thread::yield_now();
+        }
+    }
+
+    fn loads_in_flight(&self) -> bool {
+        let world = self.app.world();
+        let asset_server = world.resource::<AssetServer>();
+        let loading = |id: UntypedAssetId| {
+            matches!(asset_server.get_load_state(id), Some(LoadState::Loading))
+        };
+        world
+            .resource::<AnimationWithPathsToHandles<T>>()
+            .paths_to_handles
+            .iter()
+            .any(|(collection, animations)| {
+                loading(collection.id().untyped())
+                    || matches!(
+                        asset_server.get_recursive_dependency_load_state(collection),
+                        Some(RecursiveDependencyLoadState::Loading)
+                    )
+                    || animations.iter().flatten().any(|animation| {
+                        animation
+                            .frames
+                            .iter()
+                            .chain(&animation.masks)
+                            .any(|frame| loading(frame.id().untyped()))
+                    })
+            })
+    }
+
+    pub fn library(&self) -> &AnimationLibrary<T> {
+        self.app.world().resource::<AnimationLibrary<T>>()
+    }
+
+    pub fn spawn(
+        &mut self,
+        character: T::CharacterName,
+        animation: T::AnimationName,
+        direction: Vec3,
+    ) -> Entity {
+        self.app
+            .world_mut()
+            .spawn((
+                SpriteBundle::default(),
+                character,
+                animation,
+                MovementDirection { direction },
+            ))
+            .id()
+    }
+
+    pub fn tick(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.app.update();
+        }
+    }
+
+    pub fn current_clip(&self, entity: Entity) -> Option<&MyAnimationClip> {
+        self.app.world().get::<MyAnimationClip>(entity)
+    }
+
+    pub fn current_frame(&self, entity: Entity) -> Option<usize> {
+        self.app
+            .world()
+            .get::<AnimationTimer>(entity)
+            .map(|timer| timer.current_frame)
+    }
+}
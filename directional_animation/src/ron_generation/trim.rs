@@ -0,0 +1,153 @@
+use super::team_color::crop_mask;
+use bevy::{
+    asset::AssetPath,
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureFormat},
+    },
+};
+use thiserror::Error;
+
+/// A frame cropped to its opaque bounding box.
+/// `rect` is the region of the original frame the image was cut from.
+pub struct TrimmedFrame {
+    pub image: Image,
+    pub rect: URect,
+    /// Team colour mask cropped to the same `rect`.
+    pub mask: Option<Image>,
+}
+
+/// Texture memory before and after trimming, summed over every packed frame.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrimStats {
+    pub untrimmed_bytes: usize,
+    pub trimmed_bytes: usize,
+}
+impl TrimStats {
+    pub fn saved_bytes(&self) -> usize {
+        self.untrimmed_bytes.saturating_sub(self.trimmed_bytes)
+    }
+}
+
+fn alpha_offset(format: TextureFormat) -> Option<usize> {
+    match format {
+        TextureFormat::Rgba8Unorm
+        | TextureFormat::Rgba8UnormSrgb
+        | TextureFormat::Bgra8Unorm
+        | TextureFormat::Bgra8UnormSrgb => Some(3),
+        _ => None,
+    }
+}
+
+/// Bounding box of every pixel with a non-zero alpha.
+/// Returns `None` for formats without an 8 bit alpha channel; fully transparent
+/// frames collapse to a single pixel so they can still be packed.
+pub fn opaque_bounds(image: &Image) -> Option<URect> {
+    let alpha = alpha_offset(image.texture_descriptor.format)?;
+    let size = image.size();
+    let mut min = UVec2::MAX;
+    let mut max = UVec2::ZERO;
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let index = ((y * size.x + x) * 4) as usize + alpha;
+            if image.data.get(index).copied().unwrap_or(0) > 0 {
+                min = min.min(UVec2::new(x, y));
+                max = max.max(UVec2::new(x + 1, y + 1));
+            }
+        }
+    }
+    if min.x >= max.x || min.y >= max.y {
+        return Some(URect::new(0, 0, 1, 1));
+    }
+    Some(URect::from_corners(min, max))
+}
+
+/// Copies `rect` out of a 4 bytes per pixel image.
+pub fn crop_image(image: &Image, rect: URect) -> Image {
+    let width = image.width() as usize;
+    let size = rect.size();
+    let mut data = Vec::with_capacity((size.x * size.y * 4) as usize);
+    for y in rect.min.y..rect.max.y {
+        let start = (y as usize * width + rect.min.x as usize) * 4;
+        let end = start + size.x as usize * 4;
+        data.extend_from_slice(&image.data[start..end]);
+    }
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        image.texture_descriptor.dimension,
+        data,
+        image.texture_descriptor.format,
+        RenderAssetUsages::default(),
+    )
+}
+
+pub fn trim_frame(image: &Image) -> TrimmedFrame {
+    let full = URect::from_corners(UVec2::ZERO, image.size());
+    match opaque_bounds(image) {
+        Some(rect) if rect != full => TrimmedFrame {
+            image: crop_image(image, rect),
+            rect,
+            mask: None,
+        },
+        _ => TrimmedFrame {
+            image: image.clone(),
+            rect: full,
+            mask: None,
+        },
+    }
+}
+
+/// A frame handle without an `Image`, because its file failed to load or the image was removed.
+#[derive(Debug, Error)]
+#[error("Frame {frame} ({path:?}) did not resolve to an `Image` asset")]
+pub struct MissingFrameError {
+    pub frame: usize,
+    pub path: Option<AssetPath<'static>>,
+}
+
+/// Trims every frame, failing when one did not resolve to an `Image` so rotations of a clip
+/// never end up with different frame counts. With `trim` disabled the frames are kept whole.
+/// `masks` is either empty or holds one team colour mask per frame.
+/// `stats` only counts clips that trimmed completely.
+pub fn trim_frames(
+    handles: &[Handle<Image>],
+    masks: &[Handle<Image>],
+    textures: &Assets<Image>,
+    trim: bool,
+    stats: &mut TrimStats,
+) -> Result<Vec<TrimmedFrame>, MissingFrameError> {
+    let mut frames = Vec::with_capacity(handles.len());
+    let mut clip_stats = TrimStats::default();
+    for (index, handle) in handles.iter().enumerate() {
+        let Some(texture) = textures.get(handle) else {
+            return Err(MissingFrameError {
+                frame: index,
+                path: handle.path().cloned(),
+            });
+        };
+        let mut frame = if trim {
+            trim_frame(texture)
+        } else {
+            TrimmedFrame {
+                image: texture.clone(),
+                rect: URect::from_corners(UVec2::ZERO, texture.size()),
+                mask: None,
+            }
+        };
+        frame.mask = masks
+            .get(index)
+            .and_then(|mask| textures.get(mask))
+            .and_then(|mask| crop_mask(mask, texture.size(), frame.rect));
+        clip_stats.untrimmed_bytes += texture.data.len();
+        clip_stats.trimmed_bytes += frame.image.data.len();
+        frames.push(frame);
+    }
+    stats.untrimmed_bytes += clip_stats.untrimmed_bytes;
+    stats.trimmed_bytes += clip_stats.trimmed_bytes;
+    Ok(frames)
+}
@@ -0,0 +1,216 @@
+//! Checks an `.anim.ron` collection against the files on disk before the game loads it.
+
+use bevy::{
+    math::UVec2,
+    utils::{HashMap, HashSet},
+};
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+use super::{
+    ron_label, AnimationLoadData, AnimationLoader, AnimationLoaderError, AnimationTypes,
+    AnimationsCollection,
+};
+
+/// Something wrong with a collection. Characters, animations and rotations are given as RON.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ValidationIssue {
+    #[error("{clip}: frame {path} does not exist")]
+    MissingFile { clip: String, path: String },
+    #[error("{clip}: frame {path} could not be read: {error}")]
+    UnreadableFile {
+        clip: String,
+        path: String,
+        error: String,
+    },
+    #[error("{clip}: no frames")]
+    NoFrames { clip: String },
+    #[error("{clip}: fps is {fps}")]
+    InvalidFps { clip: String, fps: f32 },
+    #[error("{clip}: listed {count} times")]
+    DuplicateKey { clip: String, count: usize },
+    #[error("{character} {animation}: missing rotations {}", missing.join(" "))]
+    IncompleteRotations {
+        character: String,
+        animation: String,
+        missing: Vec<String>,
+    },
+    #[error("{character} {animation}: frame counts differ between rotations ({})", counts.join(", "))]
+    FrameCountMismatch {
+        character: String,
+        animation: String,
+        counts: Vec<String>,
+    },
+    #[error("{clip}: frame {path} is {found} instead of {expected}")]
+    FrameSizeMismatch {
+        clip: String,
+        path: String,
+        expected: UVec2,
+        found: UVec2,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub clips: usize,
+    pub issues: Vec<ValidationIssue>,
+}
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Clips of one character and animation.
+type ClipGroup<'a, T> = (
+    (
+        &'a <T as AnimationTypes>::CharacterName,
+        &'a <T as AnimationTypes>::AnimationName,
+    ),
+    Vec<&'a AnimationLoadData<T>>,
+);
+
+fn clip_label<T: AnimationTypes>(data: &AnimationLoadData<T>) -> String {
+    format!(
+        "{} {} {}",
+        ron_label(&data.character),
+        ron_label(&data.animation),
+        ron_label(&data.rotation)
+    )
+}
+
+/// Loads `file` with the [`AnimationLoader`] and validates it.
+/// Frame paths are resolved relative to `assets_folder`, like the asset server does.
+pub fn validate_file<T: AnimationTypes>(
+    file: &Path,
+    assets_folder: &Path,
+) -> Result<ValidationReport, AnimationLoaderError> {
+    let bytes = fs::read(file)?;
+    let collection = AnimationLoader::<T>::parse(&bytes)?;
+    Ok(validate_collection(&collection, assets_folder))
+}
+
+/// Reports missing or unreadable frames, empty clips, zero fps, duplicate keys,
+/// animations lacking rotations their character has elsewhere,
+/// rotations with different frame counts and frames of different sizes.
+pub fn validate_collection<T: AnimationTypes>(
+    collection: &AnimationsCollection<T>,
+    assets_folder: &Path,
+) -> ValidationReport {
+    let mut issues = Vec::new();
+
+    // Clips grouped by character and animation, in file order
+    let mut groups: Vec<ClipGroup<T>> = Vec::new();
+    let mut group_index = HashMap::new();
+    let mut character_rotations: HashMap<&T::CharacterName, Vec<&T::Rotation>> = HashMap::new();
+    for data in collection.animations.iter() {
+        let index = *group_index
+            .entry((&data.character, &data.animation))
+            .or_insert_with(|| {
+                groups.push(((&data.character, &data.animation), Vec::new()));
+                groups.len() - 1
+            });
+        groups[index].1.push(data);
+        let rotations = character_rotations.entry(&data.character).or_default();
+        if !rotations.contains(&&data.rotation) {
+            rotations.push(&data.rotation);
+        }
+    }
+
+    for ((character, animation), clips) in groups.iter() {
+        let character_label = ron_label(*character);
+        let animation_label = ron_label(*animation);
+
+        let mut counts: HashMap<&T::Rotation, usize> = HashMap::new();
+        for data in clips.iter() {
+            *counts.entry(&data.rotation).or_default() += 1;
+        }
+        let mut reported = HashSet::new();
+        for data in clips.iter() {
+            let count = counts[&data.rotation];
+            if count > 1 && reported.insert(&data.rotation) {
+                issues.push(ValidationIssue::DuplicateKey {
+                    clip: clip_label(*data),
+                    count,
+                });
+            }
+        }
+
+        let missing: Vec<String> = character_rotations[character]
+            .iter()
+            .filter(|rotation| !counts.contains_key(**rotation))
+            .map(|rotation| ron_label(*rotation))
+            .collect();
+        if !missing.is_empty() {
+            issues.push(ValidationIssue::IncompleteRotations {
+                character: character_label.clone(),
+                animation: animation_label.clone(),
+                missing,
+            });
+        }
+
+        if clips
+            .iter()
+            .any(|data| data.frames.len() != clips[0].frames.len())
+        {
+            issues.push(ValidationIssue::FrameCountMismatch {
+                character: character_label.clone(),
+                animation: animation_label.clone(),
+                counts: clips
+                    .iter()
+                    .map(|data| format!("{}: {}", ron_label(&data.rotation), data.frames.len()))
+                    .collect(),
+            });
+        }
+
+        // Every rotation of an animation is expected to share the frame size of its first frame
+        let mut expected_size = None;
+        for data in clips.iter() {
+            let clip = clip_label(*data);
+            if data.frames.is_empty() {
+                issues.push(ValidationIssue::NoFrames { clip: clip.clone() });
+            }
+            if !(data.fps > 0.0 && data.fps.is_finite()) {
+                issues.push(ValidationIssue::InvalidFps {
+                    clip: clip.clone(),
+                    fps: data.fps,
+                });
+            }
+            for path in data.frames.iter() {
+                let file = assets_folder.join(path);
+                if !file.is_file() {
+                    issues.push(ValidationIssue::MissingFile {
+                        clip: clip.clone(),
+                        path: path.clone(),
+                    });
+                    continue;
+                }
+                match image::image_dimensions(&file) {
+                    Ok((width, height)) => {
+                        let found = UVec2::new(width, height);
+                        let expected = *expected_size.get_or_insert(found);
+                        if found != expected {
+                            issues.push(ValidationIssue::FrameSizeMismatch {
+                                clip: clip.clone(),
+                                path: path.clone(),
+                                expected,
+                                found,
+                            });
+                        }
+                    }
+                    Err(error) => issues.push(ValidationIssue::UnreadableFile {
+                        clip: clip.clone(),
+                        path: path.clone(),
+                        error: error.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+
+    ValidationReport {
+        clips: collection.animations.len(),
+        issues,
+    }
+}
@@ -0,0 +1,352 @@
+//! Standalone preview of an [`AnimationLibrary`]: pick a character and animation, turn the
+//! direction dial that drives `find_animation`, scrub frames and change the fps.
+//! Rotations another clip of the library has but the current one lacks are drawn in red.
+
+use bevy::{prelude::*, utils::HashSet};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use super::{
+    animation_library::{AnimationLibrary, MyAnimationClip},
+    animator::{AnimationTimer, MovementDirection},
+    placeholder::rotation_direction,
+    plugin::AnimationLoadingState,
+    ron_label, AnimationTypes, DirectionalRotationMatcher,
+};
+
+const DIAL_RADIUS: f32 = 160.0;
+/// Degrees per second the dial turns while an arrow key is held.
+const DIAL_SPEED: f32 = 90.0;
+
+#[derive(Resource)]
+pub struct ViewerState<T: AnimationTypes> {
+    pub characters: Vec<T::CharacterName>,
+    pub animations: Vec<T::AnimationName>,
+    /// Every rotation in the library.
+    pub rotations: Vec<T::Rotation>,
+    pub character: usize,
+    pub animation: usize,
+    /// Screen angle of the dial, counter-clockwise from the right.
+    pub dial_degrees: f32,
+    /// Overrides the fps of the clip.
+    pub fps: Option<f32>,
+    pub paused: bool,
+}
+
+impl<T: AnimationTypes> ViewerState<T> {
+    /// Screen direction the dial points in.
+    pub fn screen_direction(&self) -> Vec2 {
+        Vec2::from_angle(self.dial_degrees.to_radians())
+    }
+
+    /// Movement vector that shows on screen like the dial.
+    pub fn direction(&self) -> Vec3 {
+        T::Rotation::screen_to_movement(self.screen_direction())
+    }
+
+    /// Rotations the selected character and animation have clips for.
+    pub fn available_rotations<'a>(
+        &'a self,
+        library: &'a AnimationLibrary<T>,
+    ) -> impl Iterator<Item = &'a T::Rotation> {
+        let character = &self.characters[self.character];
+        let animation = &self.animations[self.animation];
+        library
+            .animations
+            .keys()
+            .filter(move |key| key.character == *character && key.animation == *animation)
+            .map(|key| &key.rotation)
+    }
+}
+
+/// The entity showing the selected clip.
+#[derive(Component)]
+pub struct ViewerSubject;
+
+#[derive(Component)]
+pub struct ViewerText;
+
+pub struct AnimationViewerPlugin<T: AnimationTypes> {
+    phantom: PhantomData<T>,
+}
+
+impl<T: AnimationTypes> Default for AnimationViewerPlugin<T> {
+    fn default() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: AnimationTypes> Plugin for AnimationViewerPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AnimationLoadingState::Complete), setup_viewer::<T>);
+        app.add_systems(
+            Update,
+            (
+                viewer_input::<T>,
+                apply_viewer::<T>,
+                draw_dial::<T>,
+                update_viewer_text::<T>,
+            )
+                .chain()
+                .run_if(resource_exists::<ViewerState<T>>),
+        );
+    }
+}
+
+fn sorted_by_label<V: serde::Serialize + Eq + std::hash::Hash>(values: HashSet<V>) -> Vec<V> {
+    let mut values: Vec<V> = values.into_iter().collect();
+    values.sort_by_key(ron_label);
+    values
+}
+
+pub fn setup_viewer<T: AnimationTypes>(mut commands: Commands, library: Res<AnimationLibrary<T>>) {
+    let keys = library.animations.keys();
+    let characters = sorted_by_label(keys.clone().map(|key| key.character.clone()).collect());
+    let animations = sorted_by_label(keys.clone().map(|key| key.animation.clone()).collect());
+    let rotations = sorted_by_label(keys.map(|key| key.rotation.clone()).collect());
+    if characters.is_empty() {
+        warn!("The animation library is empty, nothing to preview");
+        return;
+    }
+
+    let state = ViewerState::<T> {
+        characters,
+        animations,
+        rotations,
+        character: 0,
+        animation: 0,
+        dial_degrees: 0.0,
+        fps: None,
+        paused: false,
+    };
+    commands.spawn((
+        SpriteBundle::default(),
+        state.characters[0].clone(),
+        state.animations[0].clone(),
+        MovementDirection {
+            direction: state.direction(),
+        },
+        ViewerSubject,
+    ));
+    commands.spawn((
+        TextBundle::from_section("", TextStyle::default()).with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Px(8.0),
+            ..default()
+        }),
+        ViewerText,
+    ));
+    commands.insert_resource(state);
+}
+
+pub fn viewer_input<T: AnimationTypes>(
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    library: Res<AnimationLibrary<T>>,
+    mut state: ResMut<ViewerState<T>>,
+    mut subject: Query<
+        (
+            &MyAnimationClip,
+            &mut AnimationTimer,
+            &mut TextureAtlas,
+            &mut Handle<Image>,
+            &mut Sprite,
+        ),
+        With<ViewerSubject>,
+    >,
+) {
+    if keys.just_pressed(KeyCode::KeyC) {
+        state.character = (state.character + 1) % state.characters.len();
+    }
+    if keys.just_pressed(KeyCode::KeyA) {
+        state.animation = (state.animation + 1) % state.animations.len();
+    }
+    if keys.pressed(KeyCode::ArrowLeft) {
+        state.dial_degrees = (state.dial_degrees + DIAL_SPEED * time.delta_seconds()) % 360.0;
+    }
+    if keys.pressed(KeyCode::ArrowRight) {
+        state.dial_degrees =
+            (state.dial_degrees - DIAL_SPEED * time.delta_seconds()).rem_euclid(360.0);
+    }
+    if keys.just_pressed(KeyCode::Tab) {
+        // Jump to the next rotation counter-clockwise
+        let mut angles: Vec<f32> = state
+            .available_rotations(&library)
+            .map(|rotation| {
+                let direction = rotation_direction(rotation);
+                direction
+                    .y
+                    .atan2(direction.x)
+                    .to_degrees()
+                    .rem_euclid(360.0)
+            })
+            .collect();
+        angles.sort_by(f32::total_cmp);
+        let current = state.dial_degrees + 0.5;
+        if let Some(next) = angles
+            .iter()
+            .find(|angle| **angle > current)
+            .or(angles.first())
+        {
+            state.dial_degrees = *next;
+        }
+    }
+    if keys.just_pressed(KeyCode::Space) {
+        state.paused = !state.paused;
+    }
+
+    let Ok((clip, mut timer, mut atlas, mut texture, mut sprite)) = subject.get_single_mut() else {
+        return;
+    };
+    let clip_fps = state.fps.unwrap_or(clip.fps);
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        state.fps = Some(clip_fps + 1.0);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        state.fps = Some((clip_fps - 1.0).max(1.0));
+    }
+    if keys.just_pressed(KeyCode::Digit0) {
+        state.fps = None;
+    }
+    let step = if keys.just_pressed(KeyCode::Period) {
+        1
+    } else if keys.just_pressed(KeyCode::Comma) {
+        clip.len - 1
+    } else {
+        0
+    };
+    if step != 0 {
+        state.paused = true;
+        timer.current_frame = (timer.current_frame + step) % clip.len;
+        let (page_texture, page_atlas) = clip.atlas_for_frame(timer.current_frame);
+        *texture = page_texture;
+        *atlas = page_atlas;
+        clip.apply_frame(timer.current_frame, &mut sprite);
+    }
+}
+
+pub fn apply_viewer<T: AnimationTypes>(
+    mut commands: Commands,
+    state: Res<ViewerState<T>>,
+    mut subject: Query<
+        (
+            Entity,
+            Option<&MyAnimationClip>,
+            Option<&mut AnimationTimer>,
+        ),
+        With<ViewerSubject>,
+    >,
+) {
+    let Ok((entity, clip, timer)) = subject.get_single_mut() else {
+        return;
+    };
+    if state.is_changed() {
+        commands.entity(entity).insert((
+            state.characters[state.character].clone(),
+            state.animations[state.animation].clone(),
+            MovementDirection {
+                direction: state.direction(),
+            },
+        ));
+    }
+    let (Some(clip), Some(mut timer)) = (clip, timer) else {
+        return;
+    };
+    let duration = Duration::from_secs_f32(1.0 / state.fps.unwrap_or(clip.fps));
+    if timer.timer.duration() != duration {
+        timer.timer.set_duration(duration);
+    }
+    if state.paused != timer.timer.paused() {
+        if state.paused {
+            timer.timer.pause();
+        } else {
+            timer.timer.unpause();
+        }
+    }
+}
+
+pub fn draw_dial<T: AnimationTypes>(
+    mut gizmos: Gizmos,
+    state: Res<ViewerState<T>>,
+    library: Res<AnimationLibrary<T>>,
+) {
+    let available: Vec<&T::Rotation> = state.available_rotations(&library).collect();
+    gizmos.circle_2d(Vec2::ZERO, DIAL_RADIUS, Color::srgb(0.5, 0.5, 0.5));
+    for rotation in state.rotations.iter() {
+        let direction = rotation_direction(rotation);
+        let color = if available.contains(&rotation) {
+            Color::srgb(0.2, 0.9, 0.3)
+        } else {
+            Color::srgb(1.0, 0.2, 0.2)
+        };
+        gizmos.line_2d(
+            direction * DIAL_RADIUS * 0.9,
+            direction * DIAL_RADIUS * 1.1,
+            color,
+        );
+    }
+    gizmos.line_2d(
+        Vec2::ZERO,
+        state.screen_direction() * DIAL_RADIUS,
+        Color::srgb(1.0, 0.9, 0.2),
+    );
+}
+
+pub fn update_viewer_text<T: AnimationTypes>(
+    state: Res<ViewerState<T>>,
+    library: Res<AnimationLibrary<T>>,
+    subject: Query<(Option<&MyAnimationClip>, Option<&AnimationTimer>), With<ViewerSubject>>,
+    mut text: Query<&mut Text, With<ViewerText>>,
+) {
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+    let direction = state.direction();
+    let available: Vec<&T::Rotation> = state.available_rotations(&library).collect();
+    let shown = available.iter().max_by(|a, b| {
+        a.get_similarity(direction)
+            .total_cmp(&b.get_similarity(direction))
+    });
+    let missing: Vec<String> = state
+        .rotations
+        .iter()
+        .filter(|rotation| !available.contains(rotation))
+        .map(ron_label)
+        .collect();
+
+    let mut lines = vec![format!(
+        "{} {}",
+        ron_label(&state.characters[state.character]),
+        ron_label(&state.animations[state.animation])
+    )];
+    match shown {
+        Some(rotation) => lines.push(format!(
+            "rotation {} (dial {:.0})",
+            ron_label(*rotation),
+            state.dial_degrees
+        )),
+        None => lines.push("no clip for this animation".to_string()),
+    }
+    if let Ok((Some(clip), Some(timer))) = subject.get_single() {
+        lines.push(format!(
+            "frame {}/{}  {:.1} fps{}",
+            timer.current_frame % clip.len + 1,
+            clip.len,
+            state.fps.unwrap_or(clip.fps),
+            if state.paused { "  paused" } else { "" }
+        ));
+    }
+    if !missing.is_empty() {
+        lines.push(format!("missing rotations: {}", missing.join(" ")));
+    }
+    lines.push(String::new());
+    lines.push(
+        "C character  A animation  Left/Right dial  Tab next rotation\n\
+         Space pause  ,/. scrub  Up/Down fps  0 clip fps"
+            .to_string(),
+    );
+    text.sections[0].value = lines.join("\n");
+}
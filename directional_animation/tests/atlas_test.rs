@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use directional_animation::ron_generation::animation_library::{
+    create_texture_atlas, create_texture_atlas_pages, FrameLocation,
+};
+use directional_animation::ron_generation::atlas::AtlasSettings;
+use directional_animation::ron_generation::team_color::key_color_mask;
+use directional_animation::ron_generation::trim::trim_frame;
+
+fn opaque_frame(size: u32) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255, 255, 255, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+#[test]
+fn test_long_clip_spills_over_pages() {
+    let frames: Vec<_> = (0..10).map(|_| trim_frame(&opaque_frame(64))).collect();
+    let settings = AtlasSettings {
+        max_size: UVec2::splat(128),
+        ..default()
+    };
+    let mut textures = Assets::<Image>::default();
+    let mut layouts = Assets::<TextureAtlasLayout>::default();
+
+    let (pages, locations, rects) =
+        create_texture_atlas_pages(&frames, &settings, &mut textures, &mut layouts).unwrap();
+
+    assert_eq!(locations.len(), 10);
+    assert_eq!(rects.len(), 10);
+    assert!(pages.len() >= 3);
+    assert_eq!(locations[0], FrameLocation { page: 0, index: 0 });
+    assert_eq!(locations.last().unwrap().page, pages.len() - 1);
+    for page in &pages {
+        let image = textures.get(&page.texture_atlas).unwrap();
+        assert!(image.width() <= 128 && image.height() <= 128);
+    }
+}
+
+#[test]
+fn test_oversized_frame_fails_the_clip() {
+    let frames = vec![
+        trim_frame(&opaque_frame(16)),
+        trim_frame(&opaque_frame(256)),
+        trim_frame(&opaque_frame(16)),
+    ];
+    let settings = AtlasSettings {
+        max_size: UVec2::splat(128),
+        ..default()
+    };
+    let mut textures = Assets::<Image>::default();
+    let mut layouts = Assets::<TextureAtlasLayout>::default();
+
+    let error =
+        create_texture_atlas_pages(&frames, &settings, &mut textures, &mut layouts).unwrap_err();
+
+    assert_eq!(error.frame, 1);
+    assert_eq!(error.size, UVec2::splat(256));
+}
+
+#[test]
+fn test_key_color_masks_share_the_frame_layout() {
+    let mut frames: Vec<_> = (0..4).map(|_| trim_frame(&opaque_frame(32))).collect();
+    for frame in frames.iter_mut() {
+        frame.mask = Some(key_color_mask(&frame.image, 300.0, 20.0, 0.5));
+    }
+    let mut textures = Assets::<Image>::default();
+
+    let (_, _, team_mask) =
+        create_texture_atlas(&frames, &AtlasSettings::default(), &mut textures).unwrap();
+
+    // White frames contain no magenta, the mask is packed but empty
+    let mask = textures.get(&team_mask.unwrap()).unwrap();
+    assert!(mask.data.chunks(4).all(|pixel| pixel[3] == 0));
+}
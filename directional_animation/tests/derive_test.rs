@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use directional_animation::ron_generation::animation_library::AnimationLibrary;
+use directional_animation::ron_generation::rotation::{AngleRotation, CompassAngles};
+use directional_animation::ron_generation::{
+    AnimationAliases, AnimationGenerationParameters, AnimationTypes,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Component, AnimationAliases)]
+pub enum DerivedCharacter {
+    #[alias("wolf", "wolf_v2")]
+    Wolf,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Component, AnimationAliases)]
+pub enum DerivedAnimation {
+    Idle,
+    #[alias("WOLK")]
+    Running,
+}
+
+#[derive(AnimationTypes)]
+#[animation(
+    character = DerivedCharacter,
+    animation = DerivedAnimation,
+    rotation = AngleRotation<CompassAngles>
+)]
+pub struct DerivedTypes;
+
+// Reflect brings its own TypePath
+#[derive(AnimationTypes, Reflect)]
+#[animation(
+    character = DerivedCharacter,
+    animation = DerivedAnimation,
+    rotation = AngleRotation<CompassAngles>,
+    type_path = false
+)]
+pub struct ReflectedTypes;
+
+#[test]
+fn test_derived_aliases() {
+    let characters = DerivedCharacter::aliases();
+    assert_eq!(characters.len(), 3);
+    assert!(characters["wolf_v2"] == DerivedCharacter::Wolf);
+
+    let params = AnimationGenerationParameters::<DerivedTypes>::from_aliases(
+        "assets",
+        "assets",
+        AngleRotation::sector_aliases(8),
+        12.,
+    );
+    assert!(params.animation_aliases["Idle"] == DerivedAnimation::Idle);
+    assert!(params.animation_aliases["WOLK"] == DerivedAnimation::Running);
+    assert_eq!(params.rotation_aliases.len(), 8);
+}
+
+#[test]
+fn test_derived_types_work_with_the_library() {
+    let library = AnimationLibrary::<DerivedTypes>::default();
+    assert!(library
+        .find_animation(&DerivedCharacter::Wolf, &DerivedAnimation::Idle, Vec3::X)
+        .is_none());
+    let derived: DerivedTypes = Default::default();
+    assert!(derived == DerivedTypes);
+    assert_eq!(
+        <DerivedTypes as bevy::reflect::TypePath>::short_type_path(),
+        "DerivedTypes"
+    );
+}
+
+#[test]
+fn test_derived_types_with_reflect() {
+    let library = AnimationLibrary::<ReflectedTypes>::default();
+    assert!(library.animations.is_empty());
+    assert_eq!(
+        <ReflectedTypes as bevy::reflect::TypePath>::short_type_path(),
+        "ReflectedTypes"
+    );
+    assert!(ReflectedTypes.reflect_partial_eq(&ReflectedTypes).is_some());
+}
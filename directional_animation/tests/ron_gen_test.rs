@@ -1,15 +1,41 @@
-use bevy::app::App;
 use bevy::prelude::*;
 use bevy::reflect::Reflect;
+use bevy::render::mesh::VertexAttributeValues;
+use bevy::sprite::Mesh2dHandle;
 use bevy::utils::HashMap;
+use directional_animation::ron_generation::animation_library::MyAnimationClip;
+use directional_animation::ron_generation::animator::{animate, FallbackAnimation};
+use directional_animation::ron_generation::binary::{from_binary, to_binary};
+use directional_animation::ron_generation::dynamic::{
+    DynamicAnimation, DynamicAnimationTypes, DynamicCharacter,
+};
+use directional_animation::ron_generation::facing::FacingTarget;
+use directional_animation::ron_generation::format::ANIMATIONS_FORMAT_VERSION;
 use directional_animation::ron_generation::generate_animations_ron::generate_animations_ron;
-use directional_animation::ron_generation::plugin::LoadAnimationPlugin;
+use directional_animation::ron_generation::placeholder::{
+    generate_placeholder_animations, rotation_direction, PlaceholderSettings,
+};
+use directional_animation::ron_generation::rotation::{
+    AngleConvention, AngleRotation, MovementPlane, Winding,
+};
+use directional_animation::ron_generation::team_color::{
+    remove_team_color_meshes, update_team_color_meshes, TeamColor, TeamColorAssets,
+    TeamColorMaterial, TeamColorSprite,
+};
+use directional_animation::ron_generation::testing::{
+    temp_assets_dir, write_synthetic_clip, AnimationTestApp, SyntheticClip,
+};
+use directional_animation::ron_generation::validate::{
+    validate_collection, validate_file, ValidationIssue,
+};
 use directional_animation::ron_generation::{
-    AnimationGenerationParameters, AnimationTypes, AnimationsCollection, DirectionalRotationMatcher,
+    AnimationGenerationParameters, AnimationLoader, AnimationTypes, AnimationsCollection,
+    SpriteMetadata,
 };
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
 
 // Test implementation of required traits
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Component)]
@@ -26,27 +52,13 @@ pub enum TestAnimation {
     Dying,
     Casting,
 }
-#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
-pub struct TestRotation(u32);
-
-impl DirectionalRotationMatcher for TestRotation {
-    fn get_similarity(&self, movement_vector: bevy::math::Vec3) -> f32 {
-        let angle = self.0 as f32;
-        let movement_angle = movement_vector.z.atan2(movement_vector.x).to_degrees();
-
-        // Normalize angles to 0-360 range
-        let movement_angle = ((movement_angle + 360.0) % 360.0) as u32 as f32;
-
-        // Calculate shortest angle difference
-        let mut difference = (angle - movement_angle).abs();
-        if difference > 180.0 {
-            difference = 360.0 - difference;
-        }
-
-        // Return normalized similarity (1.0 is perfect match, 0.0 is worst match)
-        1.0 - (difference / 180.0)
-    }
+// Angles on the ground plane, measured from +x towards +z
+pub struct TestAngles;
+impl AngleConvention for TestAngles {
+    const PLANE: MovementPlane = MovementPlane::XZ;
+    const WINDING: Winding = Winding::Clockwise;
 }
+pub type TestRotation = AngleRotation<TestAngles>;
 
 #[derive(Deserialize, Serialize, Reflect, Default, PartialEq, Eq, Hash)]
 pub struct TestTypes;
@@ -56,34 +68,6 @@ impl AnimationTypes for TestTypes {
     type Rotation = TestRotation;
 }
 
-#[derive(Resource, Default)]
-pub struct State {
-    pub collection: Handle<AnimationsCollection<TestTypes>>,
-    pub printed: bool,
-}
-
-fn _setup_test_directories(root: &str) {
-    // Create root directory
-    fs::create_dir_all(root).unwrap();
-
-    // Create test structure: root/hero/walk/front/frame1.png
-    let test_path = Path::new(root).join("hero").join("walk").join("front");
-    fs::create_dir_all(&test_path).unwrap();
-
-    // Create a test PNG file
-    fs::write(test_path.join("frame1.png"), &[]).unwrap();
-}
-fn populate_rotation_aliases() -> HashMap<String, TestRotation> {
-    let angles = "0,45,90,135,180,225,270,315";
-    let mut rotation_aliases = HashMap::new();
-
-    for angle_str in angles.split(',') {
-        let angle: u32 = angle_str.parse().unwrap();
-        rotation_aliases.insert(angle_str.to_string(), TestRotation(angle));
-    }
-
-    rotation_aliases
-}
 fn get_generation_params(test_folder: &str) -> AnimationGenerationParameters<TestTypes> {
     let mut character_aliases = HashMap::new();
     character_aliases.insert("wolf".to_string(), TestCharacter::Wolf);
@@ -93,90 +77,449 @@ fn get_generation_params(test_folder: &str) -> AnimationGenerationParameters<Tes
     animation_aliases.insert("PUNch".to_string(), TestAnimation::Attacking);
     animation_aliases.insert("abiliti".to_string(), TestAnimation::Casting);
 
-    let rotation_aliases = populate_rotation_aliases();
+    let rotation_aliases = TestRotation::sector_aliases(8);
 
     let assets_folder = test_folder;
     AnimationGenerationParameters {
         character_aliases,
         animation_aliases,
         rotation_aliases,
+        character_metadata: HashMap::new(),
         root_folder: test_folder.to_string(),
         assets_folder: assets_folder.to_string(),
         fps: 30.,
+        binary_manifest: false,
     }
 }
+fn setup_test_assets(name: &str) -> PathBuf {
+    let test_folder = temp_assets_dir(name);
+    write_synthetic_clip(
+        &test_folder,
+        &SyntheticClip {
+            character: "wolf",
+            animation: "WOLK",
+            rotations: &["0", "90", "180", "270"],
+            frames: 4,
+            size: UVec2::splat(32),
+        },
+    )
+    .unwrap();
+    let params = get_generation_params(&test_folder.to_string_lossy());
+    generate_animations_ron(params);
+    test_folder
+}
+
 #[test]
 fn test_animation_generation() {
-    let test_folder = "/run/host/var/home/f0kes/dev/bevy/bevy_rts/assets";
-    let params: AnimationGenerationParameters<TestTypes> = get_generation_params(&test_folder);
-    generate_animations_ron(params);
+    let test_folder = setup_test_assets("generation");
 
-    let ron_path = Path::new(test_folder).join("wolf.anim.ron");
+    // Verify RON file was created
+    let ron_path = test_folder.join("wolf.anim.ron");
     assert!(ron_path.exists());
 
     // Try to load and parse the RON file
     let ron_content = fs::read_to_string(&ron_path).unwrap();
     let deserialized: AnimationsCollection<TestTypes> =
-        ron::de::from_bytes(&ron_content.as_bytes()).unwrap();
+        ron::de::from_bytes(ron_content.as_bytes()).unwrap();
     let animations = &deserialized.animations;
-    let stri = animations.get(0).unwrap().rotation.0;
-
-    println!("{:?}", stri);
+    assert_eq!(animations.len(), 4);
+    assert!(animations
+        .iter()
+        .all(|animation| animation.frames.len() == 4 && !animation.frames[0].starts_with('/')));
 }
-// Verify RON file was created
 
 #[test]
 fn test_load_animation() {
-    let mut app = App::new();
-    let test_folder = "/run/host/var/home/f0kes/dev/bevy/bevy_rts/assets";
-    let _params: AnimationGenerationParameters<TestTypes> = get_generation_params(&test_folder);
-    app.add_plugins((MinimalPlugins, AssetPlugin::default()));
-    app.add_plugins(LoadAnimationPlugin::<TestTypes>::default());
-    app.add_systems(Startup, load_animations);
-    app.add_systems(Update, print_on_load);
-    app.add_systems(Update, timeout);
-    // app.init_resource::<State>();
-    app.run();
-}
-fn load_animations(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    //mut state: ResMut<State>,
-) {
-    //state.collection = asset_server.load("wolf.anim.ron");
-
-    commands.insert_resource(State {
-        collection: asset_server.load("wolf.anim.ron"),
-        printed: false,
-    });
-    println!("done");
-}
-fn print_on_load(
-    mut state: ResMut<State>,
-    custom_assets: Res<Assets<AnimationsCollection<TestTypes>>>,
-    mut exit: EventWriter<AppExit>,
-) {
-    let collection = custom_assets.get(&state.collection);
-
-    // Can't print results if the assets aren't ready
-    if state.printed {
-        return;
-    }
+    let test_folder = setup_test_assets("load");
+    let mut app = AnimationTestApp::<TestTypes>::new(
+        &test_folder,
+        vec!["wolf.anim.ron".to_string()],
+        Duration::from_secs_f32(1. / 30.),
+    );
+    let library = app.load(1000);
+    assert_eq!(library.animations.len(), 4);
+    let registry = app.app.world().resource::<AppTypeRegistry>().read();
+    assert!(registry
+        .get(std::any::TypeId::of::<MyAnimationClip>())
+        .is_some());
+    drop(registry);
+
+    // Moving along +z matches the 90 degree rotation
+    let entity = app.spawn(
+        TestCharacter::Wolf,
+        TestAnimation::Running,
+        Vec3::new(0., 0., 1.),
+    );
+    app.tick(1);
+    let clip = app.current_clip(entity).unwrap();
+    assert_eq!(clip.len, 4);
+    assert_eq!(clip.frame_size, UVec2::splat(32));
+    // Frames are trimmed to the opaque square in the middle
+    assert_eq!(clip.frame_rects[0], URect::new(8, 8, 24, 24));
+    assert_eq!(app.current_frame(entity), Some(0));
+
+    app.tick(3);
+    assert_eq!(app.current_frame(entity), Some(3));
+    app.tick(1);
+    assert_eq!(app.current_frame(entity), Some(0));
+}
+
+#[test]
+fn test_fallback_for_missing_animation() {
+    let test_folder = setup_test_assets("fallback");
+    let mut app = AnimationTestApp::<TestTypes>::new(
+        &test_folder,
+        vec!["wolf.anim.ron".to_string()],
+        Duration::from_secs_f32(1. / 30.),
+    );
+    app.app
+        .world_mut()
+        .insert_resource(FallbackAnimation::<TestTypes>(TestAnimation::Running));
+    app.load(1000);
+
+    // The wolf has no idle clip, it holds the first frame of its run
+    let entity = app.spawn(TestCharacter::Wolf, TestAnimation::Idle, Vec3::Z);
+    app.tick(4);
+    assert_eq!(app.current_clip(entity).unwrap().len, 1);
+    assert_eq!(app.current_frame(entity), Some(0));
+
+    app.app
+        .world_mut()
+        .entity_mut(entity)
+        .insert(TestAnimation::Running);
+    app.tick(1);
+    assert_eq!(app.current_clip(entity).unwrap().len, 4);
+}
+
+#[test]
+fn test_load_without_game_types() {
+    let test_folder = setup_test_assets("dynamic");
+    let mut app = AnimationTestApp::<DynamicAnimationTypes>::new(
+        &test_folder,
+        vec!["wolf.anim.ron".to_string()],
+        Duration::from_secs_f32(1. / 30.),
+    );
+    let library = app.load(1000);
+    assert_eq!(library.animations.len(), 4);
+    let character = DynamicCharacter("Wolf".to_string());
+    let animation = DynamicAnimation("Running".to_string());
+    assert!(library
+        .find_animation(&character, &animation, Vec3::X)
+        .is_some());
+    // Names are written back as enum variants
+    assert_eq!(ron::to_string(&character).unwrap(), "Wolf");
+}
+
+#[test]
+fn test_facing_target_while_strafing() {
+    let test_folder = setup_test_assets("facing");
+    let mut app = AnimationTestApp::<TestTypes>::new(
+        &test_folder,
+        vec!["wolf.anim.ron".to_string()],
+        Duration::from_secs_f32(1. / 30.),
+    );
+    app.load(1000);
+    let facing_clip = app
+        .library()
+        .find_animation(&TestCharacter::Wolf, &TestAnimation::Running, Vec3::Z)
+        .unwrap()
+        .pages[0]
+        .texture_atlas
+        .clone();
+
+    // Moving along -x while looking at a target along +z
+    let entity = app.spawn(TestCharacter::Wolf, TestAnimation::Running, Vec3::NEG_X);
+    app.app
+        .world_mut()
+        .entity_mut(entity)
+        .insert(FacingTarget::Point(Vec3::new(0., 0., 10.)));
+    app.tick(2);
+    let clip = app.current_clip(entity).unwrap();
+    assert_eq!(clip.pages[0].texture_atlas, facing_clip);
+}
+
+#[test]
+fn test_team_color_keeps_sprite_settings() {
+    let test_folder = setup_test_assets("team_color");
+    let mut app = AnimationTestApp::<TestTypes>::new(
+        &test_folder,
+        vec!["wolf.anim.ron".to_string()],
+        Duration::from_secs_f32(1. / 30.),
+    );
+    app.load(1000);
+    // The headless app has no renderer, so only the systems run
+    app.app
+        .init_asset::<Mesh>()
+        .init_asset::<TeamColorMaterial>()
+        .init_resource::<TeamColorAssets>()
+        .add_systems(
+            Update,
+            (
+                update_team_color_meshes.after(animate),
+                remove_team_color_meshes,
+            ),
+        );
+
+    let tint = Color::srgba(1., 0.5, 0.5, 0.5);
+    let entity = app.spawn(TestCharacter::Wolf, TestAnimation::Running, Vec3::Z);
+    app.app.world_mut().entity_mut(entity).insert((
+        TeamColor(Color::srgb(0., 0., 1.)),
+        Sprite {
+            color: tint,
+            flip_x: true,
+            ..default()
+        },
+    ));
+    app.tick(2);
+    let world = app.app.world();
+    assert!(world.get::<Sprite>(entity).is_none());
+    assert!(world.get::<TeamColorSprite>(entity).unwrap().0.flip_x);
+    let material = world.get::<Handle<TeamColorMaterial>>(entity).unwrap();
+    let material = world
+        .resource::<Assets<TeamColorMaterial>>()
+        .get(material)
+        .unwrap();
+    assert_eq!(material.tint, LinearRgba::from(tint));
+    let mesh = world.get::<Mesh2dHandle>(entity).unwrap();
+    let mesh = world.resource::<Assets<Mesh>>().get(&mesh.0).unwrap();
+    let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) else {
+        panic!("quad without uvs");
+    };
+    // The right corners show the left of the frame
+    assert!(uvs[0][0] < uvs[1][0]);
+
+    app.app.world_mut().entity_mut(entity).remove::<TeamColor>();
+    app.tick(2);
+    let world = app.app.world();
+    let sprite = world.get::<Sprite>(entity).unwrap();
+    assert!(sprite.flip_x);
+    assert_eq!(sprite.color, tint);
+    assert!(world.get::<TeamColorSprite>(entity).is_none());
+    assert!(world.get::<Mesh2dHandle>(entity).is_none());
+    // Nothing keeps the quad and material of the entity alive
+    assert!(world.resource::<Assets<Mesh>>().is_empty());
+    assert!(world.resource::<Assets<TeamColorMaterial>>().is_empty());
+}
+
+#[test]
+fn test_placeholder_animations() {
+    let test_folder = temp_assets_dir("placeholder");
+    let mut params = get_generation_params(&test_folder.to_string_lossy());
+    params
+        .character_aliases
+        .insert("knight".to_string(), TestCharacter::Knight);
+    let settings = PlaceholderSettings {
+        size: UVec2::splat(64),
+        frames: 3,
+    };
+    // Other characters keep their .anim.ron as it is
+    write_synthetic_clip(
+        &test_folder,
+        &SyntheticClip {
+            character: "wolf",
+            animation: "WOLK",
+            rotations: &["0"],
+            frames: 1,
+            size: UVec2::splat(16),
+        },
+    )
+    .unwrap();
+    fs::write(test_folder.join("wolf.anim.ron"), "[]").unwrap();
+    generate_placeholder_animations(params, "knight", &settings).unwrap();
+    assert_eq!(
+        fs::read_to_string(test_folder.join("wolf.anim.ron")).unwrap(),
+        "[]"
+    );
+
+    let ron_content = fs::read_to_string(test_folder.join("knight.anim.ron")).unwrap();
+    let deserialized: AnimationsCollection<TestTypes> =
+        ron::de::from_bytes(ron_content.as_bytes()).unwrap();
+    // 3 animations in 8 rotations
+    assert_eq!(deserialized.animations.len(), 24);
 
-    if collection.is_none() {
-        //println!("Custom Asset Not Ready");
-        return;
+    let mut app = AnimationTestApp::<TestTypes>::new(
+        &test_folder,
+        vec!["knight.anim.ron".to_string()],
+        Duration::from_secs_f32(1. / 30.),
+    );
+    let library = app.load(1000);
+    assert_eq!(library.animations.len(), 24);
+    assert!(library
+        .animations
+        .values()
+        .all(|clip| clip.len == 3 && clip.frame_size == UVec2::splat(64)));
+
+    // Arrows point where the rotation shows on screen, moving along +z is down
+    for degrees in (0..360).step_by(45) {
+        let expected = Vec2::from_angle(-(degrees as f32).to_radians());
+        let direction = rotation_direction(&TestRotation::new(degrees));
+        assert!(direction.angle_between(expected).abs() < 0.02, "{degrees}");
+        let frame = image::open(test_folder.join(format!("knight/WOLK/{degrees}/frame_0000.png")))
+            .unwrap()
+            .to_rgba8();
+        // Image rows go down
+        let alpha = |distance: f32| {
+            let point = Vec2::splat(32.) + Vec2::new(expected.x, -expected.y) * distance;
+            frame.get_pixel(point.x as u32, point.y as u32)[3]
+        };
+        assert!(alpha(18.) > 0, "{degrees}");
+        assert_eq!(alpha(-21.), 0, "{degrees}");
     }
 
-    let animations = &collection.unwrap().animations;
-    let stri = animations.get(0).unwrap().rotation.0;
-    println!("{:?}", stri);
-    // Once printed, we won't print again
-    state.printed = true;
-    exit.send(AppExit::Success);
+    // Failing to write the .anim.ron is an error
+    fs::remove_file(test_folder.join("knight.anim.ron")).unwrap();
+    fs::create_dir(test_folder.join("knight.anim.ron")).unwrap();
+    let mut params = get_generation_params(&test_folder.to_string_lossy());
+    params
+        .character_aliases
+        .insert("knight".to_string(), TestCharacter::Knight);
+    assert!(generate_placeholder_animations(params, "knight", &settings).is_err());
+}
+
+#[test]
+fn test_validate_collection() {
+    let test_folder = setup_test_assets("validate");
+    let ron_path = test_folder.join("wolf.anim.ron");
+    let report = validate_file::<TestTypes>(&ron_path, &test_folder).unwrap();
+    assert_eq!(report.clips, 4);
+    assert!(report.is_ok(), "{:?}", report.issues);
+
+    // An attack with a single rotation, and a running frame gone missing
+    write_synthetic_clip(
+        &test_folder,
+        &SyntheticClip {
+            character: "wolf",
+            animation: "PUNch",
+            rotations: &["0"],
+            frames: 4,
+            size: UVec2::splat(32),
+        },
+    )
+    .unwrap();
+    generate_animations_ron(get_generation_params(&test_folder.to_string_lossy()));
+    fs::remove_file(test_folder.join("wolf/WOLK/90/frame_0003.png")).unwrap();
+
+    let bytes = fs::read(&ron_path).unwrap();
+    let mut collection = AnimationLoader::<TestTypes>::parse(&bytes).unwrap();
+    collection.animations[0].fps = 0.;
+    let report = validate_collection(&collection, &test_folder);
+    assert_eq!(report.clips, 5);
+    assert_eq!(report.issues.len(), 3, "{:?}", report.issues);
+    assert!(report.issues.iter().any(|issue| matches!(
+        issue,
+        ValidationIssue::IncompleteRotations { animation, missing, .. }
+            if animation == "Attacking" && missing.len() == 3
+    )));
+    assert!(report.issues.iter().any(|issue| matches!(
+        issue,
+        ValidationIssue::MissingFile { path, .. } if path.ends_with("frame_0003.png")
+    )));
+    assert!(report
+        .issues
+        .iter()
+        .any(|issue| matches!(issue, ValidationIssue::InvalidFps { .. })));
+}
+
+#[test]
+fn test_versioned_format() {
+    let test_folder = setup_test_assets("format");
+    let ron_content = fs::read_to_string(test_folder.join("wolf.anim.ron")).unwrap();
+    let collection: AnimationsCollection<TestTypes> = ron::from_str(&ron_content).unwrap();
+    assert_eq!(collection.header.version, ANIMATIONS_FORMAT_VERSION);
+    assert_eq!(collection.header.base_path, "wolf");
+    // Paths and fps come out resolved
+    assert!(collection
+        .animations
+        .iter()
+        .all(|animation| animation.frames[0].starts_with("wolf/WOLK/") && animation.fps == 30.));
+    // The shared fps is only written in the header
+    assert_eq!(ron_content.matches("fps:").count(), 1);
+
+    // The unversioned format is a bare list
+    let legacy = r#"[(
+        character: Wolf,
+        animation: Idle,
+        rotation: (90),
+        frames: ["wolf/idle/90/frame_0000.png"],
+        fps: 12.0,
+    )]"#;
+    let collection: AnimationsCollection<TestTypes> = ron::from_str(legacy).unwrap();
+    assert_eq!(collection.header.version, 0);
+    assert_eq!(collection.animations[0].fps, 12.);
+    let migrated = ron::to_string(&collection).unwrap();
+    assert!(migrated.starts_with(&format!("(version:{}", ANIMATIONS_FORMAT_VERSION)));
+    let reread: AnimationsCollection<TestTypes> = ron::from_str(&migrated).unwrap();
+    assert_eq!(reread.animations[0].frames, collection.animations[0].frames);
+    assert_eq!(reread.animations[0].fps, 12.);
+
+    // Fields from newer versions are skipped, unknown versions refused
+    let newer = "(version: 1, fps: 8.0, shadows: (size: 3), animations: [\
+        (character: Wolf, animation: Idle, rotation: (0), frames: [\"a.png\"])])";
+    let collection: AnimationsCollection<TestTypes> = ron::from_str(newer).unwrap();
+    assert_eq!(collection.animations[0].fps, 8.);
+    let unsupported = format!(
+        "(version: {}, animations: [])",
+        ANIMATIONS_FORMAT_VERSION + 1
+    );
+    assert!(ron::from_str::<AnimationsCollection<TestTypes>>(&unsupported).is_err());
 }
-fn timeout(time: Res<Time>, mut exit: EventWriter<AppExit>) {
-    if time.elapsed_seconds_f64() > 1. {
-        exit.send(AppExit::from_code(1));
+
+#[test]
+fn test_binary_manifest_matches_ron() {
+    let test_folder = temp_assets_dir("binary");
+    write_synthetic_clip(
+        &test_folder,
+        &SyntheticClip {
+            character: "wolf",
+            animation: "WOLK",
+            rotations: &["0", "90", "180", "270"],
+            frames: 4,
+            size: UVec2::splat(32),
+        },
+    )
+    .unwrap();
+    let mut params = get_generation_params(&test_folder.to_string_lossy());
+    params.binary_manifest = true;
+    params.character_metadata.insert(
+        TestCharacter::Wolf,
+        SpriteMetadata {
+            scale: Some(0.5),
+            pivot: Some(Vec2::new(0., -0.4)),
+            ..Default::default()
+        },
+    );
+    generate_animations_ron(params);
+
+    let ron_content = fs::read_to_string(test_folder.join("wolf.anim.ron")).unwrap();
+    let from_ron: AnimationsCollection<TestTypes> = ron::from_str(&ron_content).unwrap();
+    let bytes = fs::read(test_folder.join("wolf.anim.bin")).unwrap();
+    let from_bin = from_binary::<TestTypes>(&bytes).unwrap();
+    assert_eq!(from_bin.header, from_ron.header);
+    assert_eq!(from_bin.animations.len(), from_ron.animations.len());
+    for (bin, ron) in from_bin.animations.iter().zip(from_ron.animations.iter()) {
+        assert!(bin.character == ron.character && bin.animation == ron.animation);
+        assert!(bin.rotation == ron.rotation);
+        assert_eq!(bin.frames, ron.frames);
+        assert_eq!(bin.fps, ron.fps);
+        assert_eq!(bin.metadata, ron.metadata);
     }
+    assert_eq!(to_binary(&from_ron).unwrap(), bytes);
+    assert!(from_binary::<TestTypes>(ron_content.as_bytes()).is_err());
+    // Dynamic names have no variant indices to read or write
+    assert!(from_binary::<DynamicAnimationTypes>(&bytes).is_err());
+    let dynamic: AnimationsCollection<DynamicAnimationTypes> = ron::from_str(&ron_content).unwrap();
+    assert!(to_binary(&dynamic).is_err());
+
+    // Loads through its own asset loader into the same library
+    let mut app = AnimationTestApp::<TestTypes>::new(
+        &test_folder,
+        vec!["wolf.anim.bin".to_string()],
+        Duration::from_secs_f32(1. / 30.),
+    );
+    let library = app.load(1000);
+    assert_eq!(library.animations.len(), 4);
+    assert!(library
+        .animations
+        .values()
+        .all(|clip| clip.len == 4 && clip.metadata.scale == Some(0.5)));
 }
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+use directional_animation::ron_generation::dynamic::DynamicAngles;
+use directional_animation::ron_generation::facing::project_through_camera;
+use directional_animation::ron_generation::placeholder::rotation_direction;
+use directional_animation::ron_generation::rotation::{
+    rotation_aliases_from_folders, AngleRotation, CompassAngles, IsometricCompassAngles,
+    MathAngles, YawPitchRotation,
+};
+use directional_animation::ron_generation::testing::temp_assets_dir;
+use directional_animation::ron_generation::DirectionalRotationMatcher;
+
+fn best_match<R: DirectionalRotationMatcher + Clone>(rotations: &[R], movement: Vec3) -> R {
+    rotations
+        .iter()
+        .max_by(|a, b| {
+            a.get_similarity(movement)
+                .total_cmp(&b.get_similarity(movement))
+        })
+        .unwrap()
+        .clone()
+}
+
+#[test]
+fn test_sector_conventions() {
+    let math = AngleRotation::<MathAngles>::sectors(8);
+    assert_eq!(best_match(&math, Vec3::X).degrees, 0);
+    assert_eq!(best_match(&math, Vec3::Y).degrees, 90);
+
+    let compass = AngleRotation::<CompassAngles>::sectors(4);
+    assert_eq!(best_match(&compass, Vec3::Y).degrees, 0);
+    assert_eq!(best_match(&compass, Vec3::X).degrees, 90);
+    assert_eq!(best_match(&compass, Vec3::NEG_X).degrees, 270);
+}
+
+#[test]
+fn test_dynamic_rotation_convention() {
+    // Like the game's files by default, 0 faces down
+    let rotations = AngleRotation::<DynamicAngles<270, false>>::sectors(4);
+    assert_eq!(best_match(&rotations, Vec3::NEG_Y).degrees, 0);
+    assert_eq!(best_match(&rotations, Vec3::X).degrees, 90);
+
+    let rotations = AngleRotation::<DynamicAngles<90, true>>::sectors(4);
+    assert_eq!(best_match(&rotations, Vec3::Y).degrees, 0);
+    assert_eq!(best_match(&rotations, Vec3::X).degrees, 90);
+    assert_eq!(best_match(&rotations, Vec3::NEG_X).degrees, 270);
+}
+
+#[test]
+fn test_isometric_projection() {
+    let compass = AngleRotation::<IsometricCompassAngles>::sectors(8);
+    // Moving along +x goes to the lower right of the screen
+    let rotation = best_match(&compass, Vec3::X);
+    assert_eq!(rotation.degrees, 135);
+    // Diagonals on the ground are straight on screen
+    assert_eq!(best_match(&compass, Vec3::new(-1., 1., 0.)).degrees, 0);
+    assert_eq!(best_match(&compass, Vec3::new(1., 1., 0.)).degrees, 90);
+    // Degrees are screen angles, probing them goes back through the projection
+    let direction = rotation_direction(&rotation);
+    assert!(direction.angle_between(Vec2::new(1., -1.)).abs() < 0.02);
+}
+
+#[test]
+fn test_yaw_pitch() {
+    let rotations = YawPitchRotation::grid(8, &[0, -45]);
+    assert_eq!(rotations.len(), 16);
+    let forward = best_match(&rotations, Vec3::NEG_Z);
+    assert_eq!((forward.yaw, forward.pitch), (0, 0));
+    let left_down = best_match(&rotations, Vec3::new(-1., -1.4, 0.));
+    assert_eq!((left_down.yaw, left_down.pitch), (90, -45));
+    assert_eq!(
+        YawPitchRotation::from_folder_name("45_-30"),
+        Some(YawPitchRotation {
+            yaw: 45,
+            pitch: -30
+        })
+    );
+}
+
+#[test]
+fn test_aliases_from_folder_names() {
+    let root = temp_assets_dir("rotation_aliases");
+    for rotation in ["rot_045", "NE", "south_west", "idle"] {
+        std::fs::create_dir_all(root.join("wolf").join("WOLK").join(rotation)).unwrap();
+    }
+    let aliases =
+        rotation_aliases_from_folders(&root, AngleRotation::<CompassAngles>::from_folder_name);
+    assert_eq!(aliases.len(), 3);
+    assert_eq!(aliases["rot_045"].degrees, 45);
+    assert_eq!(aliases["NE"].degrees, 45);
+    assert_eq!(aliases["south_west"].degrees, 225);
+}
+
+#[test]
+fn test_project_through_camera() {
+    // Looking straight down with -z at the top of the screen
+    let camera =
+        GlobalTransform::from(Transform::from_xyz(0., 10., 0.).looking_at(Vec3::ZERO, Vec3::NEG_Z));
+    let screen = project_through_camera(&camera, Vec3::NEG_Z);
+    assert!(screen.abs_diff_eq(Vec3::Y, 1e-5));
+    let screen = project_through_camera(&camera, Vec3::X);
+    assert!(screen.abs_diff_eq(Vec3::X, 1e-5));
+}
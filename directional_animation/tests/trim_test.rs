@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use directional_animation::ron_generation::trim::{
+    opaque_bounds, trim_frame, trim_frames, TrimStats,
+};
+
+fn image_with_opaque_rect(size: UVec2, rect: URect) -> Image {
+    let mut data = vec![0; (size.x * size.y * 4) as usize];
+    for y in rect.min.y..rect.max.y {
+        for x in rect.min.x..rect.max.x {
+            let index = ((y * size.x + x) * 4) as usize;
+            data[index..index + 4].copy_from_slice(&[255, 0, 0, 255]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+#[test]
+fn test_trim_to_opaque_bounds() {
+    let rect = URect::new(3, 5, 7, 6);
+    let image = image_with_opaque_rect(UVec2::new(10, 8), rect);
+    assert_eq!(opaque_bounds(&image), Some(rect));
+
+    let trimmed = trim_frame(&image);
+    assert_eq!(trimmed.rect, rect);
+    assert_eq!(trimmed.image.size(), UVec2::new(4, 1));
+    assert!(trimmed.image.data.chunks(4).all(|pixel| pixel[3] == 255));
+}
+
+#[test]
+fn test_trim_transparent_frame() {
+    let image = image_with_opaque_rect(UVec2::new(4, 4), URect::new(0, 0, 0, 0));
+    let trimmed = trim_frame(&image);
+    assert_eq!(trimmed.rect, URect::new(0, 0, 1, 1));
+    assert_eq!(trimmed.image.size(), UVec2::ONE);
+}
+
+#[test]
+fn test_missing_frame_fails_the_clip() {
+    let mut textures = Assets::<Image>::default();
+    let image = || image_with_opaque_rect(UVec2::splat(8), URect::new(2, 2, 6, 6));
+    let frame = textures.add(image());
+    let missing = textures.add(image());
+    textures.remove(&missing);
+
+    let mut stats = TrimStats::default();
+    let handles = [frame.clone(), missing, frame.clone()];
+    let error = trim_frames(&handles, &[], &textures, true, &mut stats)
+        .err()
+        .unwrap();
+    assert_eq!(error.frame, 1);
+    // Nothing of the failed clip is counted
+    assert_eq!(stats, TrimStats::default());
+
+    let frames = trim_frames(&[frame.clone(), frame], &[], &textures, true, &mut stats).unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(stats.untrimmed_bytes, 2 * 8 * 8 * 4);
+}
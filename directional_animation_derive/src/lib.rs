@@ -0,0 +1,184 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    parse_macro_input, punctuated::Punctuated, Data, DeriveInput, Fields, LitBool, LitStr, Token,
+    Type,
+};
+
+/// Implements `AnimationTypes` for a unit struct, together with the `Default`, `PartialEq`,
+/// `Eq`, `Hash` and `TypePath` impls the trait needs.
+/// Add `type_path = false` when the struct derives `TypePath` or `Reflect` itself.
+///
+/// ```ignore
+/// #[derive(AnimationTypes)]
+/// #[animation(character = Character, animation = AnimationType, rotation = CharacterRotation)]
+/// pub struct HiveMindAnimationTypes;
+///
+/// #[derive(AnimationTypes, Reflect)]
+/// #[animation(character = Character, animation = AnimationType, rotation = CharacterRotation)]
+/// #[animation(type_path = false)]
+/// pub struct ReflectedAnimationTypes;
+/// ```
+#[proc_macro_derive(AnimationTypes, attributes(animation))]
+pub fn derive_animation_types(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match animation_types(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn animation_types(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    match &input.data {
+        Data::Struct(data) if matches!(data.fields, Fields::Unit) => {}
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "AnimationTypes can only be derived for unit structs",
+            ))
+        }
+    }
+
+    let mut character: Option<Type> = None;
+    let mut animation: Option<Type> = None;
+    let mut rotation: Option<Type> = None;
+    let mut type_path = true;
+    for attribute in input
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("animation"))
+    {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("type_path") {
+                type_path = meta.value()?.parse::<LitBool>()?.value;
+                return Ok(());
+            }
+            let slot = if meta.path.is_ident("character") {
+                &mut character
+            } else if meta.path.is_ident("animation") {
+                &mut animation
+            } else if meta.path.is_ident("rotation") {
+                &mut rotation
+            } else {
+                return Err(
+                    meta.error("expected `character`, `animation`, `rotation` or `type_path`")
+                );
+            };
+            *slot = Some(meta.value()?.parse()?);
+            Ok(())
+        })?;
+    }
+    let missing = |key: &str| {
+        syn::Error::new_spanned(input, format!("missing `#[animation({} = ...)]`", key))
+    };
+    let character = character.ok_or_else(|| missing("character"))?;
+    let animation = animation.ok_or_else(|| missing("animation"))?;
+    let rotation = rotation.ok_or_else(|| missing("rotation"))?;
+
+    let type_path_impl = type_path.then(|| {
+        quote! {
+            impl ::bevy::reflect::TypePath for #name {
+                fn type_path() -> &'static str {
+                    ::core::concat!(::core::module_path!(), "::", ::core::stringify!(#name))
+                }
+                fn short_type_path() -> &'static str {
+                    ::core::stringify!(#name)
+                }
+                fn type_ident() -> Option<&'static str> {
+                    Some(::core::stringify!(#name))
+                }
+                fn crate_name() -> Option<&'static str> {
+                    ::core::module_path!().split("::").next()
+                }
+                fn module_path() -> Option<&'static str> {
+                    Some(::core::module_path!())
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl ::directional_animation::ron_generation::AnimationTypes for #name {
+            type CharacterName = #character;
+            type AnimationName = #animation;
+            type Rotation = #rotation;
+        }
+        impl ::core::default::Default for #name {
+            fn default() -> Self {
+                Self
+            }
+        }
+        impl ::core::cmp::PartialEq for #name {
+            fn eq(&self, _: &Self) -> bool {
+                true
+            }
+        }
+        impl ::core::cmp::Eq for #name {}
+        impl ::core::hash::Hash for #name {
+            fn hash<H: ::core::hash::Hasher>(&self, _: &mut H) {}
+        }
+        #type_path_impl
+    })
+}
+
+/// Implements `AnimationAliases` for a fieldless enum. Every variant is known by its own name
+/// and by the folder names listed in `#[alias(...)]`.
+///
+/// ```ignore
+/// #[derive(AnimationAliases)]
+/// pub enum AnimationType {
+///     #[alias("WOLK", "walk")]
+///     Running,
+/// }
+/// ```
+#[proc_macro_derive(AnimationAliases, attributes(alias))]
+pub fn derive_animation_aliases(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match animation_aliases(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn animation_aliases(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "AnimationAliases can only be derived for enums",
+        ));
+    };
+
+    let mut entries = Vec::new();
+    for variant in data.variants.iter() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "AnimationAliases variants can not have fields",
+            ));
+        }
+        let ident = &variant.ident;
+        let mut aliases = vec![LitStr::new(&ident.to_string(), ident.span())];
+        for attribute in variant.attrs.iter().filter(|a| a.path().is_ident("alias")) {
+            aliases.extend(
+                attribute.parse_args_with(Punctuated::<LitStr, Token![,]>::parse_terminated)?,
+            );
+        }
+        for alias in aliases {
+            entries.push(quote! {
+                aliases.insert(::std::string::String::from(#alias), #name::#ident);
+            });
+        }
+    }
+
+    Ok(quote! {
+        impl ::directional_animation::ron_generation::AnimationAliases for #name {
+            fn aliases() -> ::bevy::utils::HashMap<::std::string::String, Self> {
+                let mut aliases = ::bevy::utils::HashMap::new();
+                #(#entries)*
+                aliases
+            }
+        }
+    })
+}
@@ -0,0 +1,21 @@
+//! Generates placeholder frames and a matching `.anim.ron` for a character without art.
+//!
+//! `cargo run --example generate_placeholders -- knight`
+//!
+//! Add `<character>.anim.ron` to the paths of `LoadAnimationPlugin` to use the clips in game.
+
+use bevy_game::animation_defintions::get_generation_params;
+use directional_animation::ron_generation::placeholder::{
+    generate_placeholder_animations, PlaceholderSettings,
+};
+
+fn main() {
+    let character = std::env::args().nth(1).unwrap_or("knight".to_string());
+    let params = get_generation_params("assets");
+    if let Err(e) =
+        generate_placeholder_animations(params, &character, &PlaceholderSettings::default())
+    {
+        eprintln!("Could not generate placeholders for {}: {}", character, e);
+        std::process::exit(1);
+    }
+}
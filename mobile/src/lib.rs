@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 use bevy::window::WindowMode;
+use bevy_game::camera::CameraSettings;
 use bevy_game::GamePlugin; // ToDo: Replace bevy_game with your new crate name.
 
 #[bevy_main]
@@ -16,5 +17,10 @@ fn main() {
             }),
             GamePlugin,
         ))
+        // Pan with two fingers instead, a finger resting at the edge isn't a cursor
+        .insert_resource(CameraSettings {
+            edge_pan: false,
+            ..default()
+        })
         .run();
 }
@@ -0,0 +1,377 @@
+use bevy::input::gamepad::{GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// How far a gamepad axis has to be pushed to count as pressed.
+const AXIS_THRESHOLD: f32 = 0.5;
+
+/// Something the player does, bound to inputs in the [`InputMap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum GameAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Click or drag to select units.
+    Select,
+    /// Click or drag to order the selected units to a place.
+    Command,
+    /// Held to add to the selection or queue orders instead of replacing them.
+    Append,
+    AttackMove,
+    Patrol,
+    HoldPosition,
+    Stop,
+    CancelOrder,
+    CycleFormation,
+    PlaceBuilding,
+    /// Held to drag the map around.
+    DragCamera,
+    FollowSelected,
+    /// Held with a control group to store the selection in it.
+    AssignGroup,
+    ControlGroup(u8),
+}
+
+impl GameAction {
+    /// Every action, in the order the controls menu lists them.
+    pub fn all() -> Vec<GameAction> {
+        let mut actions = vec![
+            GameAction::Up,
+            GameAction::Down,
+            GameAction::Left,
+            GameAction::Right,
+            GameAction::Select,
+            GameAction::Command,
+            GameAction::Append,
+            GameAction::AttackMove,
+            GameAction::Patrol,
+            GameAction::HoldPosition,
+            GameAction::Stop,
+            GameAction::CancelOrder,
+            GameAction::CycleFormation,
+            GameAction::PlaceBuilding,
+            GameAction::DragCamera,
+            GameAction::FollowSelected,
+            GameAction::AssignGroup,
+        ];
+        actions.extend((0..10).map(GameAction::ControlGroup));
+        actions
+    }
+}
+
+impl fmt::Display for GameAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameAction::ControlGroup(group) => write!(f, "Control group {}", group),
+            action => write!(f, "{:?}", action),
+        }
+    }
+}
+
+/// An input an action can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    GamepadButton(GamepadButtonType),
+    /// Pressed while the axis is pushed past half way, toward positive values or negative ones.
+    GamepadAxis {
+        axis: GamepadAxisType,
+        positive: bool,
+    },
+}
+
+impl InputBinding {
+    fn is_gamepad(&self) -> bool {
+        matches!(
+            self,
+            InputBinding::GamepadButton(_) | InputBinding::GamepadAxis { .. }
+        )
+    }
+}
+
+impl fmt::Display for InputBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputBinding::Key(key) => {
+                let name = format!("{:?}", key);
+                let name = name
+                    .strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name);
+                f.write_str(name)
+            }
+            InputBinding::Mouse(button) => write!(f, "Mouse {:?}", button),
+            InputBinding::GamepadButton(button) => write!(f, "Pad {:?}", button),
+            InputBinding::GamepadAxis { axis, positive } => {
+                write!(f, "Pad {:?}{}", axis, if *positive { "+" } else { "-" })
+            }
+        }
+    }
+}
+
+/// Inputs of every [`GameAction`], loaded from and saved to a RON settings file.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputMap {
+    pub bindings: BTreeMap<GameAction, Vec<InputBinding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use GameAction::*;
+        use InputBinding::{GamepadButton as Pad, Key, Mouse};
+        let axis = |axis, positive| InputBinding::GamepadAxis { axis, positive };
+        let mut bindings = BTreeMap::from([
+            (
+                Up,
+                vec![
+                    Key(KeyCode::KeyW),
+                    Key(KeyCode::ArrowUp),
+                    Pad(GamepadButtonType::DPadUp),
+                    axis(GamepadAxisType::LeftStickY, true),
+                ],
+            ),
+            (
+                Down,
+                vec![
+                    Key(KeyCode::KeyS),
+                    Key(KeyCode::ArrowDown),
+                    Pad(GamepadButtonType::DPadDown),
+                    axis(GamepadAxisType::LeftStickY, false),
+                ],
+            ),
+            (
+                Left,
+                vec![
+                    Key(KeyCode::KeyA),
+                    Key(KeyCode::ArrowLeft),
+                    Pad(GamepadButtonType::DPadLeft),
+                    axis(GamepadAxisType::LeftStickX, false),
+                ],
+            ),
+            (
+                Right,
+                vec![
+                    Key(KeyCode::KeyD),
+                    Key(KeyCode::ArrowRight),
+                    Pad(GamepadButtonType::DPadRight),
+                    axis(GamepadAxisType::LeftStickX, true),
+                ],
+            ),
+            (Select, vec![Mouse(MouseButton::Left)]),
+            (Command, vec![Mouse(MouseButton::Right)]),
+            (
+                Append,
+                vec![
+                    Key(KeyCode::ShiftLeft),
+                    Key(KeyCode::ShiftRight),
+                    Pad(GamepadButtonType::LeftTrigger),
+                ],
+            ),
+            (AttackMove, vec![Key(KeyCode::KeyT)]),
+            (Patrol, vec![Key(KeyCode::KeyP)]),
+            (
+                HoldPosition,
+                vec![Key(KeyCode::KeyH), Pad(GamepadButtonType::West)],
+            ),
+            (Stop, vec![Key(KeyCode::KeyX), Pad(GamepadButtonType::East)]),
+            (CancelOrder, vec![Key(KeyCode::Escape)]),
+            (
+                CycleFormation,
+                vec![Key(KeyCode::KeyF), Pad(GamepadButtonType::RightTrigger)],
+            ),
+            (PlaceBuilding, vec![Key(KeyCode::KeyB)]),
+            (DragCamera, vec![Mouse(MouseButton::Middle)]),
+            (
+                FollowSelected,
+                vec![Key(KeyCode::Space), Pad(GamepadButtonType::North)],
+            ),
+            (
+                AssignGroup,
+                vec![Key(KeyCode::ControlLeft), Key(KeyCode::ControlRight)],
+            ),
+        ]);
+        let digits = [
+            KeyCode::Digit0,
+            KeyCode::Digit1,
+            KeyCode::Digit2,
+            KeyCode::Digit3,
+            KeyCode::Digit4,
+            KeyCode::Digit5,
+            KeyCode::Digit6,
+            KeyCode::Digit7,
+            KeyCode::Digit8,
+            KeyCode::Digit9,
+        ];
+        for (group, digit) in digits.into_iter().enumerate() {
+            bindings.insert(ControlGroup(group as u8), vec![Key(digit)]);
+        }
+        InputMap { bindings }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum InputMapError {
+    #[error("Could not read or write the input settings: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse the input settings: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Could not write the input settings: {0}")]
+    RonWrite(#[from] ron::Error),
+}
+
+impl InputMap {
+    pub fn bindings(&self, action: GameAction) -> &[InputBinding] {
+        self.bindings.get(&action).map_or(&[], |bindings| bindings)
+    }
+
+    /// Binds `action` to `binding`, replacing its other bindings of the same kind,
+    /// keyboard and mouse or gamepad.
+    pub fn rebind(&mut self, action: GameAction, binding: InputBinding) {
+        let bindings = self.bindings.entry(action).or_default();
+        bindings.retain(|other| other.is_gamepad() != binding.is_gamepad());
+        bindings.insert(0, binding);
+    }
+
+    /// Inputs bound to more than one action, with those actions.
+    pub fn conflicts(&self) -> Vec<(InputBinding, Vec<GameAction>)> {
+        let mut conflicts: Vec<(InputBinding, Vec<GameAction>)> = Vec::new();
+        for (action, bindings) in self.bindings.iter() {
+            for binding in bindings {
+                match conflicts.iter_mut().find(|(other, _)| other == binding) {
+                    Some((_, actions)) => actions.push(*action),
+                    None => conflicts.push((*binding, vec![*action])),
+                }
+            }
+        }
+        conflicts.retain(|(_, actions)| actions.len() > 1);
+        conflicts
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, InputMapError> {
+        let mut map: InputMap = ron::from_str(text)?;
+        // Actions added since the file was saved get their default bindings
+        for (action, bindings) in InputMap::default().bindings {
+            map.bindings.entry(action).or_insert(bindings);
+        }
+        Ok(map)
+    }
+
+    pub fn to_ron(&self) -> Result<String, InputMapError> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, InputMapError> {
+        InputMap::from_ron(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), InputMapError> {
+        if let Some(folder) = path.parent() {
+            std::fs::create_dir_all(folder)?;
+        }
+        std::fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+}
+
+/// Which actions are held this frame, from the [`InputMap`].
+#[derive(Resource, Default, Debug)]
+pub struct ActionState {
+    pressed: HashSet<GameAction>,
+    previous: HashSet<GameAction>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: GameAction) -> bool {
+        self.pressed.contains(&action)
+    }
+    pub fn just_pressed(&self, action: GameAction) -> bool {
+        self.pressed.contains(&action) && !self.previous.contains(&action)
+    }
+    pub fn just_released(&self, action: GameAction) -> bool {
+        !self.pressed.contains(&action) && self.previous.contains(&action)
+    }
+    /// How much the player pushes toward `positive` rather than `negative`, from -1 to 1.
+    pub fn axis(&self, negative: GameAction, positive: GameAction) -> f32 {
+        let value = |action| if self.pressed(action) { 1. } else { 0. };
+        value(positive) - value(negative)
+    }
+}
+
+pub(crate) fn update_action_state(
+    input_map: Res<InputMap>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut state: ResMut<ActionState>,
+) {
+    let binding_pressed = |binding: &InputBinding| match *binding {
+        InputBinding::Key(key) => keyboard.pressed(key),
+        InputBinding::Mouse(button) => mouse.pressed(button),
+        InputBinding::GamepadButton(button) => gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.pressed(GamepadButton::new(gamepad, button))),
+        InputBinding::GamepadAxis { axis, positive } => gamepads.iter().any(|gamepad| {
+            let value = gamepad_axes
+                .get(GamepadAxis::new(gamepad, axis))
+                .unwrap_or(0.);
+            if positive {
+                value >= AXIS_THRESHOLD
+            } else {
+                value <= -AXIS_THRESHOLD
+            }
+        }),
+    };
+    let pressed = input_map
+        .bindings
+        .iter()
+        .filter(|(_, bindings)| bindings.iter().any(binding_pressed))
+        .map(|(action, _)| *action)
+        .collect();
+    state.previous = std::mem::replace(&mut state.pressed, pressed);
+}
+
+/// The first input pressed this frame, for binding it to an action.
+pub(crate) fn just_pressed_binding(
+    keyboard: &ButtonInput<KeyCode>,
+    mouse: &ButtonInput<MouseButton>,
+    gamepads: &Gamepads,
+    gamepad_buttons: &ButtonInput<GamepadButton>,
+    gamepad_axes: &Axis<GamepadAxis>,
+) -> Option<InputBinding> {
+    if let Some(key) = keyboard.get_just_pressed().next() {
+        return Some(InputBinding::Key(*key));
+    }
+    if let Some(button) = mouse.get_just_pressed().next() {
+        return Some(InputBinding::Mouse(*button));
+    }
+    if let Some(button) = gamepad_buttons.get_just_pressed().next() {
+        return Some(InputBinding::GamepadButton(button.button_type));
+    }
+    gamepads.iter().find_map(|gamepad| {
+        [
+            GamepadAxisType::LeftStickX,
+            GamepadAxisType::LeftStickY,
+            GamepadAxisType::RightStickX,
+            GamepadAxisType::RightStickY,
+        ]
+        .into_iter()
+        .find_map(|axis| {
+            let value = gamepad_axes.get(GamepadAxis::new(gamepad, axis))?;
+            (value.abs() >= AXIS_THRESHOLD).then_some(InputBinding::GamepadAxis {
+                axis,
+                positive: value > 0.,
+            })
+        })
+    })
+}
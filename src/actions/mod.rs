@@ -1,60 +1,166 @@
-use bevy::math::Vec3Swizzles;
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 
-use crate::actions::game_control::{get_movement, GameControl};
-use crate::player::Player;
+use crate::actions::input_map::{update_action_state, ActionState, GameAction, InputMap};
 use crate::GameState;
+use bevy::input::InputSystem;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
 
-mod game_control;
+pub mod input_map;
 
-pub const FOLLOW_EPSILON: f32 = 5.;
+/// Distance in logical pixels a finger may move before its touch no longer counts as a tap.
+const TAP_DISTANCE: f32 = 12.;
+/// File the rebound controls are kept in, inside the game's folder of [`settings_folder`].
+pub const INPUT_SETTINGS_FILE: &str = "input_map.ron";
 
 pub struct ActionsPlugin;
 
 // This plugin listens for keyboard input and converts the input into Actions
 // Actions can then be used as a resource in other systems to act on the player input.
+// Inputs are turned into actions with the InputMap, which is loaded from the settings file if there is one
+// Single finger taps are passed on for selecting and ordering units on touch screens
 impl Plugin for ActionsPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Actions>().add_systems(
-            Update,
-            set_movement_actions.run_if(in_state(GameState::Playing)),
-        );
+        app.insert_resource(load_input_map())
+            .init_resource::<ActionState>()
+            .init_resource::<Actions>()
+            .add_systems(PreUpdate, update_action_state.after(InputSystem))
+            .add_systems(
+                Update,
+                (set_movement_actions, set_cursor_actions).run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
+/// Where the rebound controls are kept, None if the platform has no folder for settings.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn input_settings_path() -> Option<PathBuf> {
+    settings_folder().map(|folder| {
+        folder
+            .join(env!("CARGO_PKG_NAME"))
+            .join(INPUT_SETTINGS_FILE)
+    })
+}
+
+/// The app's private storage, Android apps have no home folder.
+#[cfg(target_os = "android")]
+pub fn settings_folder() -> Option<PathBuf> {
+    bevy::winit::ANDROID_APP.get()?.internal_data_path()
+}
+
+#[cfg(target_os = "windows")]
+pub fn settings_folder() -> Option<PathBuf> {
+    env_folder("APPDATA")
+}
+
+/// On iOS the home folder is the app's sandbox.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn settings_folder() -> Option<PathBuf> {
+    env_folder("HOME").map(|home| home.join("Library/Application Support"))
+}
+
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_arch = "wasm32"
+)))]
+pub fn settings_folder() -> Option<PathBuf> {
+    env_folder("XDG_CONFIG_HOME").or_else(|| env_folder("HOME").map(|home| home.join(".config")))
+}
+
+/// Folder in the environment variable `name`, relative ones are ignored like XDG asks.
+#[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+fn env_folder(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|folder| folder.is_absolute())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_input_map() -> InputMap {
+    let Some(path) = input_settings_path().filter(|path| path.exists()) else {
+        return InputMap::default();
+    };
+    InputMap::load(&path).unwrap_or_else(|error| {
+        warn!("Using the default controls: {error}");
+        InputMap::default()
+    })
+}
+
+/// Keeps rebound controls for the next start.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_input_map(input_map: &InputMap) {
+    let Some(path) = input_settings_path() else {
+        warn!("Found no folder for settings, the controls are only kept until the game closes");
+        return;
+    };
+    if let Err(error) = input_map.save(&path) {
+        warn!("Failed to save the controls to {}: {error}", path.display());
+    }
+}
+
+// There is no settings file on the web
+#[cfg(target_arch = "wasm32")]
+fn load_input_map() -> InputMap {
+    InputMap::default()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save_input_map(_input_map: &InputMap) {}
+
 #[derive(Default, Resource)]
 pub struct Actions {
-    pub player_movement: Option<Vec2>,
+    /// Direction to pan the camera in from the movement keys.
+    pub camera_pan: Option<Vec2>,
+    /// World position of the mouse cursor, if it is over the window.
+    pub cursor_position: Option<Vec2>,
+    /// World position of a single finger tap that ended this frame.
+    pub tap: Option<Vec2>,
 }
 
-pub fn set_movement_actions(
+pub fn set_movement_actions(mut actions: ResMut<Actions>, action_state: Res<ActionState>) {
+    let movement = Vec2::new(
+        action_state.axis(GameAction::Left, GameAction::Right),
+        action_state.axis(GameAction::Down, GameAction::Up),
+    );
+    actions.camera_pan = movement.try_normalize();
+}
+
+pub fn set_cursor_actions(
     mut actions: ResMut<Actions>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    touch_input: Res<Touches>,
-    player: Query<&Transform, With<Player>>,
+    touches: Res<Touches>,
+    mut multi_touch: Local<bool>,
+    window: Query<&Window, With<PrimaryWindow>>,
     camera: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
 ) {
-    let mut player_movement = Vec2::new(
-        get_movement(GameControl::Right, &keyboard_input)
-            - get_movement(GameControl::Left, &keyboard_input),
-        get_movement(GameControl::Up, &keyboard_input)
-            - get_movement(GameControl::Down, &keyboard_input),
-    );
+    let camera = camera.get_single().ok();
+    let to_world = |screen: Vec2| {
+        camera.and_then(|(camera, camera_transform)| {
+            camera.viewport_to_world_2d(camera_transform, screen)
+        })
+    };
+    actions.cursor_position = window
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .and_then(to_world);
 
-    if let Some(touch_position) = touch_input.first_pressed_position() {
-        let (camera, camera_transform) = camera.single();
-        if let Some(touch_position) = camera.viewport_to_world_2d(camera_transform, touch_position)
-        {
-            let diff = touch_position - player.single().translation.xy();
-            if diff.length() > FOLLOW_EPSILON {
-                player_movement = diff.normalize();
-            }
-        }
+    // Lifting the fingers of a pinch or a two finger drag is no tap
+    if touches.iter().count() > 1 {
+        *multi_touch = true;
     }
-
-    if player_movement != Vec2::ZERO {
-        actions.player_movement = Some(player_movement.normalize());
-    } else {
-        actions.player_movement = None;
+    let mut released = touches.iter_just_released();
+    let tap = match (released.next(), released.next()) {
+        (Some(touch), None) if !*multi_touch && touch.distance().length() <= TAP_DISTANCE => {
+            Some(touch.position())
+        }
+        _ => None,
+    };
+    if touches.iter().next().is_none() {
+        *multi_touch = false;
     }
+    actions.tap = tap.and_then(to_world);
 }
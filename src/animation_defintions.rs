@@ -1,82 +1,57 @@
-use bevy::{prelude::*, utils::HashMap};
+use bevy::prelude::*;
+use directional_animation::ron_generation::rotation::{AngleConvention, AngleRotation};
 use directional_animation::ron_generation::{
-    AnimationGenerationParameters, AnimationTypes, DirectionalRotationMatcher,
+    AnimationAliases, AnimationGenerationParameters, AnimationTypes, SpriteMetadata,
 };
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Component)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Component, AnimationAliases)]
 pub enum Character {
+    #[alias("wolf")]
     Wolf,
+    #[alias("knight")]
     Knight,
 }
 
-#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Component)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Component, AnimationAliases)]
 pub enum AnimationType {
+    #[alias("idle")]
     Idle,
+    #[alias("WOLK")]
     Running,
+    #[alias("PUNch")]
     Attacking,
+    #[alias("dying")]
     Dying,
+    #[alias("abiliti")]
     Casting,
 }
-#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
-pub struct CharacterRotation(u32);
-impl DirectionalRotationMatcher for CharacterRotation {
-    fn get_similarity(&self, movement_vector: bevy::math::Vec3) -> f32 {
-        let angle = self.0 as f32;
-        let movement_angle = movement_vector.x.atan2(-movement_vector.y).to_degrees();
-
-        // Normalize angles to 0-360 range
-        let movement_angle = ((movement_angle + 360.0) % 360.0) as u32 as f32;
-
-        // Calculate shortest angle difference
-        let mut difference = (angle - movement_angle).abs();
-        if difference > 180.0 {
-            difference = 360.0 - difference;
-        }
-
-        // Return normalized similarity (1.0 is perfect match, 0.0 is worst match)
-        1.0 - (difference / 180.0)
-    }
+/// Rotation folders are named in degrees, 0 faces down the screen and angles grow counter-clockwise.
+pub struct CharacterAngles;
+impl AngleConvention for CharacterAngles {
+    const ZERO_ANGLE: f32 = 270.0;
 }
+pub type CharacterRotation = AngleRotation<CharacterAngles>;
 
-#[derive(Deserialize, Serialize, Reflect, Default, PartialEq, Eq, Hash)]
+#[derive(AnimationTypes)]
+#[animation(character = Character, animation = AnimationType, rotation = CharacterRotation)]
 pub struct HiveMindAnimationTypes;
-impl AnimationTypes for HiveMindAnimationTypes {
-    type CharacterName = Character;
-    type AnimationName = AnimationType;
-    type Rotation = CharacterRotation;
-}
-fn populate_rotation_aliases() -> HashMap<String, CharacterRotation> {
-    let angles = "0,45,90,135,180,225,270,315";
-    let mut rotation_aliases = HashMap::new();
-
-    for angle_str in angles.split(',') {
-        let angle: u32 = angle_str.parse().unwrap();
-        rotation_aliases.insert(angle_str.to_string(), CharacterRotation(angle));
-    }
 
-    rotation_aliases
-}
-fn get_generation_params(
+pub fn get_generation_params(
     test_folder: &str,
 ) -> AnimationGenerationParameters<HiveMindAnimationTypes> {
-    let mut character_aliases = HashMap::new();
-    character_aliases.insert("wolf".to_string(), Character::Wolf);
-
-    let mut animation_aliases = HashMap::new();
-    animation_aliases.insert("WOLK".to_string(), AnimationType::Running);
-    animation_aliases.insert("PUNch".to_string(), AnimationType::Attacking);
-    animation_aliases.insert("abiliti".to_string(), AnimationType::Casting);
-
-    let rotation_aliases = populate_rotation_aliases();
-
-    let assets_folder = test_folder;
-    AnimationGenerationParameters {
-        character_aliases,
-        animation_aliases,
-        rotation_aliases,
-        root_folder: test_folder.to_string(),
-        assets_folder: assets_folder.to_string(),
-        fps: 30.,
-    }
+    let mut params = AnimationGenerationParameters::from_aliases(
+        test_folder,
+        test_folder,
+        CharacterRotation::sector_aliases(8),
+        30.,
+    );
+    params.character_metadata.insert(
+        Character::Wolf,
+        SpriteMetadata {
+            scale: Some(0.2),
+            ..default()
+        },
+    );
+    params
 }
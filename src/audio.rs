@@ -1,5 +1,7 @@
-use crate::actions::{set_movement_actions, Actions};
 use crate::loading::AudioAssets;
+use crate::orders::move_units;
+use crate::steering::Steering;
+use crate::units::Unit;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
@@ -7,6 +9,7 @@ use bevy_kira_audio::prelude::*;
 pub struct InternalAudioPlugin;
 
 // This plugin is responsible to control the game audio
+// The flying sound plays while any unit walks somewhere
 impl Plugin for InternalAudioPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(AudioPlugin)
@@ -14,7 +17,7 @@ impl Plugin for InternalAudioPlugin {
             .add_systems(
                 Update,
                 control_flying_sound
-                    .after(set_movement_actions)
+                    .after(move_units)
                     .run_if(in_state(GameState::Playing)),
             );
     }
@@ -34,19 +37,22 @@ fn start_audio(mut commands: Commands, audio_assets: Res<AudioAssets>, audio: Re
 }
 
 fn control_flying_sound(
-    actions: Res<Actions>,
+    units: Query<&Steering, With<Unit>>,
     audio: Res<FlyingAudio>,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
 ) {
+    let moving = units
+        .iter()
+        .any(|steering| steering.preferred != Vec2::ZERO);
     if let Some(instance) = audio_instances.get_mut(&audio.0) {
         match instance.state() {
             PlaybackState::Paused { .. } => {
-                if actions.player_movement.is_some() {
+                if moving {
                     instance.resume(AudioTween::default());
                 }
             }
             PlaybackState::Playing { .. } => {
-                if actions.player_movement.is_none() {
+                if !moving {
                     instance.pause(AudioTween::default());
                 }
             }
@@ -0,0 +1,252 @@
+use crate::actions::input_map::{ActionState, GameAction};
+use crate::actions::{set_cursor_actions, set_movement_actions, Actions};
+use crate::navigation::NavGrid;
+use crate::selection::Selected;
+use crate::GameState;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+/// Pixels of scrolling that count as one line of the mouse wheel.
+const PIXELS_PER_LINE: f32 = 100.;
+
+pub struct RtsCameraPlugin;
+
+#[derive(Resource, Clone, Debug)]
+pub struct CameraSettings {
+    /// Panning speed in screen pixels per second, so it feels the same at every zoom.
+    pub pan_speed: f32,
+    /// Pan when the cursor is this close to the window edge, in pixels.
+    pub edge_margin: f32,
+    /// Off on touch screens, which have no cursor resting at the edge.
+    pub edge_pan: bool,
+    /// Smallest and largest projection scale, smaller is closer.
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// Zoom change per line of the mouse wheel.
+    pub zoom_step: f32,
+    /// Area the camera stays in, the navigation grid when unset.
+    pub bounds: Option<Rect>,
+    /// How quickly the camera catches up with followed units, per second.
+    pub follow_speed: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        CameraSettings {
+            pan_speed: 800.,
+            edge_margin: 16.,
+            edge_pan: true,
+            min_zoom: 0.5,
+            max_zoom: 3.,
+            zoom_step: 0.1,
+            bounds: None,
+            follow_speed: 5.,
+        }
+    }
+}
+
+/// The camera the player looks at the map with.
+#[derive(Component, Default, Debug)]
+pub struct RtsCamera {
+    /// Keeps the selected units in the middle of the screen until the camera is panned.
+    pub following: bool,
+    /// World position grabbed with the middle mouse button.
+    drag_anchor: Option<Vec2>,
+}
+
+/// This plugin moves the camera with the movement keys, the screen edges and the middle mouse
+/// button, zooms with the mouse wheel or by pinching, and follows the selected units with Space
+/// The camera is kept inside the map
+impl Plugin for RtsCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraSettings>().add_systems(
+            Update,
+            (pan_camera, zoom_camera, follow_selected, clamp_camera)
+                .chain()
+                .after(set_movement_actions)
+                .before(set_cursor_actions)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// World position shown at `screen`, in logical pixels from the top left of the window.
+fn screen_to_world(window: &Window, camera: &Transform, scale: f32, screen: Vec2) -> Vec2 {
+    let offset = (screen - window.size() / 2.) * Vec2::new(1., -1.);
+    camera.translation.truncate() + offset * scale
+}
+
+fn pan_camera(
+    time: Res<Time>,
+    actions: Res<Actions>,
+    settings: Res<CameraSettings>,
+    action_state: Res<ActionState>,
+    touches: Res<Touches>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut camera: Query<(&mut Transform, &OrthographicProjection, &mut RtsCamera)>,
+) {
+    let (Ok(window), Ok((mut transform, projection, mut camera))) =
+        (window.get_single(), camera.get_single_mut())
+    else {
+        return;
+    };
+    let scale = projection.scale;
+    let cursor = window.cursor_position();
+
+    let mut direction = actions.camera_pan.unwrap_or(Vec2::ZERO);
+    if let (true, true, Some(cursor)) = (settings.edge_pan, window.focused, cursor) {
+        let margin = settings.edge_margin;
+        let size = window.size();
+        if cursor.x <= margin {
+            direction.x -= 1.;
+        } else if cursor.x >= size.x - margin {
+            direction.x += 1.;
+        }
+        // Screen y grows downwards
+        if cursor.y <= margin {
+            direction.y += 1.;
+        } else if cursor.y >= size.y - margin {
+            direction.y -= 1.;
+        }
+    }
+    let mut moved = direction != Vec2::ZERO;
+    transform.translation +=
+        (direction.normalize_or_zero() * settings.pan_speed * scale * time.delta_seconds())
+            .extend(0.);
+
+    // Keep the grabbed point under the cursor
+    if action_state.just_pressed(GameAction::DragCamera) {
+        camera.drag_anchor =
+            cursor.map(|cursor| screen_to_world(window, &transform, scale, cursor));
+    }
+    if !action_state.pressed(GameAction::DragCamera) {
+        camera.drag_anchor = None;
+    }
+    if let (Some(anchor), Some(cursor)) = (camera.drag_anchor, cursor) {
+        let grabbed = screen_to_world(window, &transform, scale, cursor);
+        transform.translation += (anchor - grabbed).extend(0.);
+        moved = true;
+    }
+
+    // Two fingers drag the map along
+    if let [first, second] = touches.iter().collect::<Vec<_>>()[..] {
+        let previous = (first.previous_position() + second.previous_position()) / 2.;
+        let current = (first.position() + second.position()) / 2.;
+        transform.translation += ((previous - current) * Vec2::new(1., -1.) * scale).extend(0.);
+        moved = true;
+    }
+
+    if moved {
+        camera.following = false;
+    }
+}
+
+fn zoom_camera(
+    settings: Res<CameraSettings>,
+    mut wheel: EventReader<MouseWheel>,
+    touches: Res<Touches>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<RtsCamera>>,
+) {
+    let (Ok(window), Ok((mut transform, mut projection))) =
+        (window.get_single(), camera.get_single_mut())
+    else {
+        return;
+    };
+    let mut zoom = 1.;
+    for event in wheel.read() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        };
+        // Scrolling up zooms in
+        zoom *= (1. - settings.zoom_step).powf(lines);
+    }
+    let mut focus = window.cursor_position();
+    if let [first, second] = touches.iter().collect::<Vec<_>>()[..] {
+        let previous = first
+            .previous_position()
+            .distance(second.previous_position());
+        let current = first.position().distance(second.position());
+        if previous > 0. && current > 0. {
+            zoom *= previous / current;
+            focus = Some((first.position() + second.position()) / 2.);
+        }
+    }
+    if zoom == 1. {
+        return;
+    }
+
+    let scale = (projection.scale * zoom).clamp(settings.min_zoom, settings.max_zoom);
+    // Zoom toward the cursor or the pinch, keeping the point under it in place
+    if let Some(focus) = focus {
+        let before = screen_to_world(window, &transform, projection.scale, focus);
+        let after = screen_to_world(window, &transform, scale, focus);
+        transform.translation += (before - after).extend(0.);
+    }
+    projection.scale = scale;
+}
+
+fn follow_selected(
+    time: Res<Time>,
+    action_state: Res<ActionState>,
+    settings: Res<CameraSettings>,
+    selected: Query<&Transform, (With<Selected>, Without<RtsCamera>)>,
+    mut camera: Query<(&mut Transform, &mut RtsCamera)>,
+) {
+    let Ok((mut transform, mut camera)) = camera.get_single_mut() else {
+        return;
+    };
+    if action_state.just_pressed(GameAction::FollowSelected) {
+        camera.following = !camera.following;
+    }
+    let count = selected.iter().count();
+    if !camera.following || count == 0 {
+        return;
+    }
+    let center = selected
+        .iter()
+        .map(|unit| unit.translation.truncate())
+        .sum::<Vec2>()
+        / count as f32;
+    let step = (settings.follow_speed * time.delta_seconds()).min(1.);
+    let position = transform.translation.truncate().lerp(center, step);
+    transform.translation = position.extend(transform.translation.z);
+}
+
+fn clamp_camera(
+    settings: Res<CameraSettings>,
+    grid: Option<Res<NavGrid>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    mut camera: Query<(&mut Transform, &OrthographicProjection), With<RtsCamera>>,
+) {
+    let (Ok(window), Ok((mut transform, projection))) =
+        (window.get_single(), camera.get_single_mut())
+    else {
+        return;
+    };
+    let Some(bounds) = settings.bounds.or_else(|| grid.map(|grid| grid.bounds())) else {
+        return;
+    };
+    // Keep the view inside the bounds, centered on them when it is wider
+    let half_view = window.size() * projection.scale / 2.;
+    let min = bounds.min + half_view;
+    let max = bounds.max - half_view;
+    let position = transform.translation.truncate();
+    let clamped = Vec2::new(
+        if min.x <= max.x {
+            position.x.clamp(min.x, max.x)
+        } else {
+            bounds.center().x
+        },
+        if min.y <= max.y {
+            position.y.clamp(min.y, max.y)
+        } else {
+            bounds.center().y
+        },
+    );
+    if clamped != position {
+        transform.translation = clamped.extend(transform.translation.z);
+    }
+}
@@ -0,0 +1,275 @@
+use crate::actions::input_map::{just_pressed_binding, GameAction, InputMap};
+use crate::actions::save_input_map;
+use crate::menu::{ButtonColors, Menu};
+use crate::GameState;
+use bevy::input::gamepad::{GamepadAxis, GamepadButton};
+use bevy::prelude::*;
+
+const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const CONFLICT_COLOR: Color = Color::srgb(1.0, 0.4, 0.3);
+
+pub struct ControlsMenuPlugin;
+
+/// Opens and closes the controls panel.
+#[derive(Component)]
+pub(crate) struct OpenControls;
+
+#[derive(Component)]
+struct ControlsPanel;
+
+/// Waits for an input to bind the action to when pressed.
+#[derive(Component)]
+struct RebindButton(GameAction);
+
+#[derive(Component)]
+struct BindingText(GameAction);
+
+#[derive(Component)]
+struct ConflictText;
+
+#[derive(Component)]
+struct ResetControls;
+
+/// The action waiting for an input, if any.
+#[derive(Resource, Default)]
+struct Rebinding {
+    action: Option<GameAction>,
+    /// The click that started rebinding has been let go, so it isn't taken as the new binding.
+    armed: bool,
+}
+
+/// This plugin adds a panel to the menu listing the bindings of every action
+/// Clicking an action binds it to the next key, mouse or gamepad button, clicking it again cancels
+/// Inputs bound to several actions are shown in red, and changes are saved right away
+impl Plugin for ControlsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>().add_systems(
+            Update,
+            (
+                toggle_controls,
+                click_controls,
+                capture_binding,
+                update_binding_texts,
+            )
+                .chain()
+                .run_if(in_state(GameState::Menu)),
+        );
+    }
+}
+
+fn text_style(font_size: f32) -> TextStyle {
+    TextStyle {
+        font_size,
+        color: TEXT_COLOR,
+        ..default()
+    }
+}
+
+fn toggle_controls(
+    mut commands: Commands,
+    mut rebinding: ResMut<Rebinding>,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<OpenControls>)>,
+    panel: Query<Entity, With<ControlsPanel>>,
+) {
+    if !buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        return;
+    }
+    *rebinding = Rebinding::default();
+    if let Ok(panel) = panel.get_single() {
+        commands.entity(panel).despawn_recursive();
+        return;
+    }
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.),
+                    top: Val::Px(10.),
+                    height: Val::Percent(85.),
+                    flex_direction: FlexDirection::Column,
+                    flex_wrap: FlexWrap::Wrap,
+                    padding: UiRect::all(Val::Px(8.)),
+                    column_gap: Val::Px(8.),
+                    ..default()
+                },
+                background_color: Color::srgba(0.05, 0.05, 0.05, 0.9).into(),
+                ..default()
+            },
+            ControlsPanel,
+            Menu,
+        ))
+        .with_children(|panel| {
+            for action in GameAction::all() {
+                panel
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(300.),
+                                padding: UiRect::axes(Val::Px(6.), Val::Px(2.)),
+                                margin: UiRect::bottom(Val::Px(2.)),
+                                ..default()
+                            },
+                            background_color: ButtonColors::default().normal.into(),
+                            ..default()
+                        },
+                        ButtonColors::default(),
+                        RebindButton(action),
+                    ))
+                    .with_children(|button| {
+                        button.spawn((
+                            TextBundle::from_section("", text_style(15.)),
+                            BindingText(action),
+                        ));
+                    });
+            }
+            panel
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(300.),
+                            padding: UiRect::all(Val::Px(6.)),
+                            margin: UiRect::top(Val::Px(6.)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        background_color: ButtonColors::default().normal.into(),
+                        ..default()
+                    },
+                    ButtonColors::default(),
+                    ResetControls,
+                ))
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section(
+                        "Reset to defaults",
+                        text_style(16.),
+                    ));
+                });
+            panel.spawn((
+                TextBundle::from_section("", text_style(14.)).with_style(Style {
+                    width: Val::Px(300.),
+                    margin: UiRect::top(Val::Px(6.)),
+                    ..default()
+                }),
+                ConflictText,
+            ));
+        });
+}
+
+fn click_controls(
+    mut rebinding: ResMut<Rebinding>,
+    mut input_map: ResMut<InputMap>,
+    rebind_buttons: Query<(&Interaction, &RebindButton), Changed<Interaction>>,
+    reset_buttons: Query<&Interaction, (Changed<Interaction>, With<ResetControls>)>,
+) {
+    for (interaction, button) in rebind_buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        // Any key can be bound, so cancelling is done with the button itself
+        *rebinding = if rebinding.action == Some(button.0) {
+            Rebinding::default()
+        } else {
+            Rebinding {
+                action: Some(button.0),
+                armed: false,
+            }
+        };
+    }
+    if reset_buttons
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        *rebinding = Rebinding::default();
+        *input_map = InputMap::default();
+        save_input_map(&input_map);
+    }
+}
+
+fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    mut input_map: ResMut<InputMap>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+) {
+    let Some(action) = rebinding.action else {
+        return;
+    };
+    if !rebinding.armed {
+        if mouse.get_pressed().next().is_none() {
+            rebinding.armed = true;
+        }
+        return;
+    }
+    let Some(binding) = just_pressed_binding(
+        &keyboard,
+        &mouse,
+        &gamepads,
+        &gamepad_buttons,
+        &gamepad_axes,
+    ) else {
+        return;
+    };
+    *rebinding = Rebinding::default();
+    input_map.rebind(action, binding);
+    save_input_map(&input_map);
+}
+
+fn update_binding_texts(
+    input_map: Res<InputMap>,
+    rebinding: Res<Rebinding>,
+    mut binding_texts: Query<(&mut Text, &BindingText), Without<ConflictText>>,
+    mut conflict_text: Query<&mut Text, With<ConflictText>>,
+) {
+    let conflicts = input_map.conflicts();
+    for (mut text, BindingText(action)) in binding_texts.iter_mut() {
+        let bindings = if rebinding.action == Some(*action) {
+            "press an input, click to cancel".to_string()
+        } else {
+            let bindings: Vec<String> = input_map
+                .bindings(*action)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            bindings.join(", ")
+        };
+        let value = format!("{action}: {bindings}");
+        let color = if conflicts
+            .iter()
+            .any(|(_, actions)| actions.contains(action))
+        {
+            CONFLICT_COLOR
+        } else {
+            TEXT_COLOR
+        };
+        let section = &text.sections[0];
+        if section.value != value || section.style.color != color {
+            let section = &mut text.sections[0];
+            section.value = value;
+            section.style.color = color;
+        }
+    }
+
+    let Ok(mut text) = conflict_text.get_single_mut() else {
+        return;
+    };
+    let value = conflicts
+        .iter()
+        .map(|(binding, actions)| {
+            let actions: Vec<String> = actions.iter().map(ToString::to_string).collect();
+            format!("{binding} is bound to {}", actions.join(" and "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if text.sections[0].value != value {
+        let section = &mut text.sections[0];
+        section.value = value;
+        section.style.color = CONFLICT_COLOR;
+    }
+}
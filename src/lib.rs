@@ -1,17 +1,33 @@
-#![allow(clippy::type_complexity)]
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
 
-mod actions;
+pub mod actions;
 mod audio;
+pub mod camera;
+mod controls_menu;
 mod loading;
 mod menu;
+pub mod navigation;
+pub mod orders;
 mod player;
+pub mod selection;
+pub mod spatial_index;
+pub mod steering;
+pub mod units;
 pub mod animation_defintions;
 
 use crate::actions::ActionsPlugin;
 use crate::audio::InternalAudioPlugin;
+use crate::camera::RtsCameraPlugin;
+use crate::controls_menu::ControlsMenuPlugin;
 use crate::loading::LoadingPlugin;
 use crate::menu::MenuPlugin;
+use crate::navigation::NavigationPlugin;
+use crate::orders::OrdersPlugin;
 use crate::player::PlayerPlugin;
+use crate::selection::SelectionPlugin;
+use crate::spatial_index::SpatialIndexPlugin;
+use crate::steering::SteeringPlugin;
+use crate::units::UnitsPlugin;
 
 use bevy::app::App;
 #[cfg(debug_assertions)]
@@ -40,9 +56,17 @@ impl Plugin for GamePlugin {
         app.init_state::<GameState>().add_plugins((
             LoadingPlugin,
             MenuPlugin,
+            ControlsMenuPlugin,
             ActionsPlugin,
+            RtsCameraPlugin,
             InternalAudioPlugin,
             PlayerPlugin,
+            UnitsPlugin,
+            SpatialIndexPlugin,
+            SelectionPlugin,
+            OrdersPlugin,
+            NavigationPlugin,
+            SteeringPlugin,
         ));
 
         #[cfg(debug_assertions)]
@@ -6,10 +6,12 @@ use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use bevy::winit::WinitWindows;
 use bevy::DefaultPlugins;
-use bevy_game::animation_defintions::HiveMindAnimationTypes;
+use bevy_game::animation_defintions::{AnimationType, HiveMindAnimationTypes};
 use bevy_game::GamePlugin;
 use directional_animation::ron_generation::plugin::{AnimatePlugin, LoadAnimationPlugin};
+use directional_animation::ron_generation::editor::AnimationLibraryWindow;
 use bevy_editor_pls::prelude::*;
+use bevy_editor_pls::AddEditorWindow;
 
 use std::io::Cursor;
 use winit::window::Icon;
@@ -39,8 +41,13 @@ fn main() {
 
     app.add_plugins(DefaultPlugins.set(window_plugin).set(asset_plugin));
     app.add_plugins(EditorPlugin::default());
+    app.add_editor_window::<AnimationLibraryWindow<HiveMindAnimationTypes>>();
 
-    app.add_plugins(AnimatePlugin::<HiveMindAnimationTypes>::default());
+    // The wolf has no idle clip, it stands on the first frame of its run instead
+    app.add_plugins(
+        AnimatePlugin::<HiveMindAnimationTypes>::default()
+            .with_fallback_animation(AnimationType::Running),
+    );
     app.add_plugins(GamePlugin);
     app.add_systems(Startup, set_window_icon);
 
@@ -1,3 +1,5 @@
+use crate::camera::RtsCamera;
+use crate::controls_menu::OpenControls;
 use crate::loading::TextureAssets;
 use crate::GameState;
 use bevy::prelude::*;
@@ -15,9 +17,9 @@ impl Plugin for MenuPlugin {
 }
 
 #[derive(Component)]
-struct ButtonColors {
-    normal: Color,
-    hovered: Color,
+pub(crate) struct ButtonColors {
+    pub(crate) normal: Color,
+    pub(crate) hovered: Color,
 }
 
 impl Default for ButtonColors {
@@ -30,11 +32,11 @@ impl Default for ButtonColors {
 }
 
 #[derive(Component)]
-struct Menu;
+pub(crate) struct Menu;
 
 fn setup_menu(mut commands: Commands, textures: Res<TextureAssets>) {
     info!("menu");
-    commands.spawn(Camera2dBundle::default());
+    commands.spawn((Camera2dBundle::default(), RtsCamera::default()));
     commands
         .spawn((
             NodeBundle {
@@ -78,6 +80,33 @@ fn setup_menu(mut commands: Commands, textures: Res<TextureAssets>) {
                         },
                     ));
                 });
+            children
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(140.0),
+                            height: Val::Px(40.0),
+                            margin: UiRect::top(Val::Px(10.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: ButtonColors::default().normal.into(),
+                        ..Default::default()
+                    },
+                    ButtonColors::default(),
+                    OpenControls,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Controls",
+                        TextStyle {
+                            font_size: 24.0,
+                            color: Color::linear_rgb(0.9, 0.9, 0.9),
+                            ..default()
+                        },
+                    ));
+                });
         });
     commands
         .spawn((
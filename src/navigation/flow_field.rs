@@ -0,0 +1,164 @@
+use super::NavGrid;
+use bevy::prelude::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f32::consts::SQRT_2;
+
+/// Width in cells of the square sectors flow fields lead to.
+pub const SECTOR_SIZE: u32 = 4;
+
+const NEIGHBOURS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+struct Candidate {
+    cost: f32,
+    cell: UVec2,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// The way to a sector from every cell of the grid, shared by all units heading there.
+pub struct FlowField {
+    pub sector: UVec2,
+    /// The grid the field was computed on.
+    pub grid: NavGrid,
+    /// Cost of reaching the sector from each cell, infinite where it can't be.
+    costs: Vec<f32>,
+    /// Unit step toward the sector from each cell, zero inside it or where it can't be reached.
+    directions: Vec<Vec2>,
+}
+
+impl FlowField {
+    /// The sector around `position`.
+    pub fn sector_of(grid: &NavGrid, position: Vec2) -> UVec2 {
+        grid.clamped_cell_at(position) / SECTOR_SIZE
+    }
+
+    /// Dijkstra outwards from the walkable cells of `sector`.
+    pub fn compute(grid: &NavGrid, sector: UVec2) -> Self {
+        let mut costs = vec![f32::INFINITY; grid.costs.len()];
+        let mut open = BinaryHeap::new();
+        let min = sector * SECTOR_SIZE;
+        let max = (min + SECTOR_SIZE).min(grid.size);
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                let cell = UVec2::new(x, y);
+                if grid.is_walkable(cell) {
+                    costs[grid.index(cell)] = 0.;
+                    open.push(Candidate { cost: 0., cell });
+                }
+            }
+        }
+
+        while let Some(Candidate { cost, cell }) = open.pop() {
+            if cost > costs[grid.index(cell)] {
+                continue;
+            }
+            for next in walkable_neighbours(grid, cell) {
+                // A unit at `next` walks on to `cell`, paying to cross `next` on the way
+                let next_cost = cost + step_length(cell, next) * grid.cost(next) as f32;
+                let index = grid.index(next);
+                if next_cost < costs[index] {
+                    costs[index] = next_cost;
+                    open.push(Candidate {
+                        cost: next_cost,
+                        cell: next,
+                    });
+                }
+            }
+        }
+
+        let directions = grid
+            .cells()
+            .map(|cell| {
+                let cost = costs[grid.index(cell)];
+                if cost == 0. || cost.is_infinite() {
+                    return Vec2::ZERO;
+                }
+                // The cheapest way on, counting what crossing this cell costs along each step
+                let through = |next: UVec2| {
+                    costs[grid.index(next)] + step_length(cell, next) * grid.cost(cell) as f32
+                };
+                walkable_neighbours(grid, cell)
+                    .min_by(|a, b| through(*a).total_cmp(&through(*b)))
+                    .map(|next| (next.as_vec2() - cell.as_vec2()).normalize())
+                    .unwrap_or(Vec2::ZERO)
+            })
+            .collect();
+        FlowField {
+            sector,
+            grid: grid.clone(),
+            costs,
+            directions,
+        }
+    }
+
+    pub fn contains(&self, position: Vec2) -> bool {
+        self.grid
+            .cell_at(position)
+            .is_some_and(|cell| cell / SECTOR_SIZE == self.sector)
+    }
+
+    /// Which way to walk from `position`, None if the sector can't be reached from there.
+    pub fn direction_at(&self, position: Vec2) -> Option<Vec2> {
+        let index = self.grid.index(self.grid.clamped_cell_at(position));
+        let direction = self.directions[index];
+        (direction != Vec2::ZERO).then_some(direction)
+    }
+
+    /// Cost of reaching the sector from `position`, infinite if it can't be.
+    pub fn cost_at(&self, position: Vec2) -> f32 {
+        self.costs[self.grid.index(self.grid.clamped_cell_at(position))]
+    }
+}
+
+/// Length of the step between neighbouring cells, longer on diagonals.
+fn step_length(from: UVec2, to: UVec2) -> f32 {
+    if from.x != to.x && from.y != to.y {
+        SQRT_2
+    } else {
+        1.
+    }
+}
+
+/// Walkable neighbours of `cell`, moving diagonally only past walkable corners.
+fn walkable_neighbours(grid: &NavGrid, cell: UVec2) -> impl Iterator<Item = UVec2> + '_ {
+    let walkable = move |dx, dy| {
+        grid.offset(cell, dx, dy)
+            .filter(|next| grid.is_walkable(*next))
+    };
+    NEIGHBOURS.iter().filter_map(move |offset| {
+        let next = walkable(offset.x, offset.y)?;
+        if offset.x != 0 && offset.y != 0 {
+            walkable(offset.x, 0)?;
+            walkable(0, offset.y)?;
+        }
+        Some(next)
+    })
+}
@@ -0,0 +1,392 @@
+use crate::actions::input_map::{ActionState, GameAction};
+use crate::actions::{set_cursor_actions, Actions};
+use crate::orders::MoveTo;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
+use bevy::utils::{HashMap, HashSet};
+use flow_field::FlowField;
+use pathfinding::{find_path, smooth_path};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+pub mod flow_field;
+pub mod pathfinding;
+
+/// Cost of a cell units can't enter.
+pub const BLOCKED: u8 = u8::MAX;
+/// Paths started per frame, the rest wait for the next frames.
+const MAX_PATH_REQUESTS_PER_FRAME: usize = 64;
+/// Units heading to the same sector before they share a flow field instead of each searching a path.
+pub const FLOW_FIELD_GROUP_SIZE: usize = 8;
+/// Side of the buildings placed with B, in world units.
+const BUILDING_SIZE: f32 = 64.;
+
+pub struct NavigationPlugin;
+
+/// Walkable cells of the map and how costly they are to cross.
+/// Cheap to clone, so path searches can run on a snapshot off the main thread.
+#[derive(Resource, Clone, Debug)]
+pub struct NavGrid {
+    /// World position of the bottom left corner of the first cell.
+    pub origin: Vec2,
+    pub cell_size: f32,
+    pub size: UVec2,
+    /// Row by row from the bottom, 1 for open ground up to [`BLOCKED`].
+    costs: Arc<Vec<u8>>,
+    /// Changes whenever a cost does, so paths on an older grid are searched again.
+    revision: u32,
+}
+
+impl Default for NavGrid {
+    fn default() -> Self {
+        let size = UVec2::splat(64);
+        let cell_size = 32.;
+        NavGrid::new(-size.as_vec2() * cell_size / 2., cell_size, size)
+    }
+}
+
+impl NavGrid {
+    /// An open grid of `size` cells.
+    pub fn new(origin: Vec2, cell_size: f32, size: UVec2) -> Self {
+        NavGrid {
+            origin,
+            cell_size,
+            size,
+            costs: Arc::new(vec![1; (size.x * size.y) as usize]),
+            revision: 0,
+        }
+    }
+
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    pub fn cell_at(&self, position: Vec2) -> Option<UVec2> {
+        let cell = ((position - self.origin) / self.cell_size).floor();
+        (cell.cmpge(Vec2::ZERO).all() && cell.cmplt(self.size.as_vec2()).all())
+            .then(|| cell.as_uvec2())
+    }
+
+    /// The cell at `position`, or the closest one on the edge when it is outside the grid.
+    pub fn clamped_cell_at(&self, position: Vec2) -> UVec2 {
+        ((position - self.origin) / self.cell_size)
+            .floor()
+            .clamp(Vec2::ZERO, (self.size - 1).as_vec2())
+            .as_uvec2()
+    }
+
+    pub fn cell_center(&self, cell: UVec2) -> Vec2 {
+        self.origin + (cell.as_vec2() + 0.5) * self.cell_size
+    }
+
+    /// The area the grid covers.
+    pub fn bounds(&self) -> Rect {
+        Rect::from_corners(
+            self.origin,
+            self.origin + self.size.as_vec2() * self.cell_size,
+        )
+    }
+
+    pub fn cell_rect(&self, cell: UVec2) -> Rect {
+        Rect::from_center_size(self.cell_center(cell), Vec2::splat(self.cell_size))
+    }
+
+    pub fn cost(&self, cell: UVec2) -> u8 {
+        self.costs[self.index(cell)]
+    }
+
+    pub fn is_walkable(&self, cell: UVec2) -> bool {
+        self.cost(cell) != BLOCKED
+    }
+
+    pub fn set_cost(&mut self, cell: UVec2, cost: u8) {
+        let index = self.index(cell);
+        let cost = cost.max(1);
+        if self.costs[index] != cost {
+            Arc::make_mut(&mut self.costs)[index] = cost;
+            self.revision = self.revision.wrapping_add(1);
+        }
+    }
+
+    /// Sets the cost of every cell overlapping `area`.
+    pub fn set_area_cost(&mut self, area: Rect, cost: u8) {
+        let min = self.clamped_cell_at(area.min);
+        // Cells the area only touches on their edge are left alone
+        let max = self.clamped_cell_at(area.max - Vec2::splat(self.cell_size * 1e-3));
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                self.set_cost(UVec2::new(x, y), cost);
+            }
+        }
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = UVec2> + '_ {
+        (0..self.size.y).flat_map(move |y| (0..self.size.x).map(move |x| UVec2::new(x, y)))
+    }
+
+    fn index(&self, cell: UVec2) -> usize {
+        (cell.y * self.size.x + cell.x) as usize
+    }
+
+    fn cell_of_index(&self, index: usize) -> UVec2 {
+        UVec2::new(index as u32 % self.size.x, index as u32 / self.size.x)
+    }
+
+    fn offset(&self, cell: UVec2, dx: i32, dy: i32) -> Option<UVec2> {
+        let next = cell.as_ivec2() + IVec2::new(dx, dy);
+        (next.cmpge(IVec2::ZERO).all() && next.cmplt(self.size.as_ivec2()).all())
+            .then(|| next.as_uvec2())
+    }
+}
+
+/// Points left to walk through on the way to a [`MoveTo`] target.
+#[derive(Component, Debug)]
+pub struct Path {
+    pub goal: Vec2,
+    pub waypoints: VecDeque<Vec2>,
+    revision: u32,
+}
+
+/// A path being searched on a background thread.
+#[derive(Component)]
+struct PathTask {
+    goal: Vec2,
+    revision: u32,
+    task: Task<Vec<Vec2>>,
+}
+
+/// Leads a unit to its [`MoveTo`] with the shared flow field of the sector around it.
+#[derive(Component, Debug)]
+pub struct FollowFlowField {
+    pub goal: Vec2,
+    pub sector: UVec2,
+}
+
+/// Flow fields in use, by sector, and the ones being computed.
+#[derive(Resource, Default)]
+pub struct FlowFields {
+    fields: HashMap<UVec2, Arc<FlowField>>,
+    tasks: HashMap<UVec2, (u32, Task<FlowField>)>,
+}
+
+impl FlowFields {
+    /// The latest field for `sector`, which may be from an older grid while the new one is computed.
+    pub fn get(&self, sector: UVec2) -> Option<&FlowField> {
+        self.fields.get(&sector).map(|field| field.as_ref())
+    }
+}
+
+/// Blocks the navigation grid under it.
+#[derive(Component, Debug)]
+pub struct Building {
+    pub size: Vec2,
+}
+
+/// This plugin keeps the navigation grid and finds paths around obstacles for units with a [`MoveTo`]
+/// Paths are searched on the async compute pool and searched again when the grid changes
+/// Large groups heading to the same place share one flow field instead
+/// B places a building at the cursor, which blocks the grid under it
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavGrid>()
+            .init_resource::<FlowFields>()
+            .add_systems(OnEnter(GameState::Playing), build_obstacles)
+            .add_systems(
+                Update,
+                (
+                    place_buildings.after(set_cursor_actions),
+                    block_buildings,
+                    request_paths,
+                    poll_path_tasks,
+                    update_flow_fields,
+                    draw_obstacles,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn build_obstacles(mut grid: ResMut<NavGrid>) {
+    // A wall to the right of the wolves and another above the player, with a muddy patch between
+    grid.set_area_cost(
+        Rect::from_corners(Vec2::new(224., -320.), Vec2::new(256., 320.)),
+        BLOCKED,
+    );
+    grid.set_area_cost(
+        Rect::from_corners(Vec2::new(-416., 192.), Vec2::new(128., 224.)),
+        BLOCKED,
+    );
+    grid.set_area_cost(
+        Rect::from_corners(Vec2::new(-320., 0.), Vec2::new(-160., 128.)),
+        4,
+    );
+}
+
+fn place_buildings(
+    mut commands: Commands,
+    action_state: Res<ActionState>,
+    actions: Res<Actions>,
+    grid: Res<NavGrid>,
+) {
+    let (true, Some(cursor)) = (
+        action_state.just_pressed(GameAction::PlaceBuilding),
+        actions.cursor_position,
+    ) else {
+        return;
+    };
+    // Line up with the cells so the building blocks exactly its footprint
+    let corner = grid.cell_rect(grid.clamped_cell_at(cursor)).min;
+    let size = Vec2::splat(BUILDING_SIZE);
+    commands.spawn((
+        Building { size },
+        SpatialBundle::from_transform(Transform::from_translation((corner + size / 2.).extend(0.))),
+    ));
+}
+
+fn block_buildings(
+    mut grid: ResMut<NavGrid>,
+    buildings: Query<(&Building, &Transform), Added<Building>>,
+) {
+    for (building, transform) in buildings.iter() {
+        grid.set_area_cost(
+            Rect::from_center_size(transform.translation.truncate(), building.size),
+            BLOCKED,
+        );
+    }
+}
+
+fn request_paths(
+    mut commands: Commands,
+    grid: Res<NavGrid>,
+    units: Query<(
+        Entity,
+        &Transform,
+        &MoveTo,
+        Option<&Path>,
+        Option<&PathTask>,
+        Option<&FollowFlowField>,
+    )>,
+) {
+    let mut group_sizes = HashMap::<UVec2, usize>::default();
+    for (_, _, move_to, ..) in units.iter() {
+        *group_sizes
+            .entry(FlowField::sector_of(&grid, move_to.0))
+            .or_default() += 1;
+    }
+
+    let pool = AsyncComputeTaskPool::get();
+    let mut requests = 0;
+    for (entity, transform, move_to, path, task, flow) in units.iter() {
+        let goal = move_to.0;
+        // Units keep their flow field until they arrive, even as their group gets smaller
+        if flow.is_some_and(|flow| flow.goal == goal) {
+            continue;
+        }
+        let sector = FlowField::sector_of(&grid, goal);
+        if group_sizes[&sector] >= FLOW_FIELD_GROUP_SIZE {
+            commands
+                .entity(entity)
+                .remove::<(Path, PathTask)>()
+                .insert(FollowFlowField { goal, sector });
+            continue;
+        }
+
+        let outdated = match (path, task) {
+            (_, Some(task)) => task.goal != goal || task.revision != grid.revision,
+            (Some(path), None) => path.goal != goal || path.revision != grid.revision,
+            (None, None) => true,
+        };
+        if !outdated || requests == MAX_PATH_REQUESTS_PER_FRAME {
+            continue;
+        }
+        requests += 1;
+        let snapshot = grid.clone();
+        let start = transform.translation.truncate();
+        let task = pool.spawn(async move {
+            // Units stuck in a blocked cell walk straight out
+            find_path(&snapshot, start, goal)
+                .map(|path| smooth_path(&snapshot, &path))
+                .unwrap_or_else(|| vec![start, goal])
+        });
+        commands
+            .entity(entity)
+            .remove::<FollowFlowField>()
+            .insert(PathTask {
+                goal,
+                revision: grid.revision,
+                task,
+            });
+    }
+}
+
+fn poll_path_tasks(mut commands: Commands, mut tasks: Query<(Entity, &mut PathTask)>) {
+    for (entity, mut task) in tasks.iter_mut() {
+        let Some(points) = block_on(poll_once(&mut task.task)) else {
+            continue;
+        };
+        commands.entity(entity).remove::<PathTask>().insert(Path {
+            goal: task.goal,
+            // The first point is where the unit stood
+            waypoints: points.into_iter().skip(1).collect(),
+            revision: task.revision,
+        });
+    }
+}
+
+/// Computes the fields units follow, again when the grid changes, and drops the ones no one follows.
+fn update_flow_fields(
+    grid: Res<NavGrid>,
+    mut flow_fields: ResMut<FlowFields>,
+    followers: Query<&FollowFlowField, With<MoveTo>>,
+) {
+    let flow_fields = flow_fields.as_mut();
+    let sectors: HashSet<UVec2> = followers.iter().map(|flow| flow.sector).collect();
+    flow_fields
+        .fields
+        .retain(|sector, _| sectors.contains(sector));
+    flow_fields
+        .tasks
+        .retain(|sector, _| sectors.contains(sector));
+
+    // Finished fields replace the ones units followed meanwhile
+    let fields = &mut flow_fields.fields;
+    flow_fields.tasks.retain(|sector, (_, task)| {
+        let Some(field) = block_on(poll_once(task)) else {
+            return true;
+        };
+        fields.insert(*sector, Arc::new(field));
+        false
+    });
+
+    let pool = AsyncComputeTaskPool::get();
+    for sector in sectors {
+        let current = flow_fields
+            .fields
+            .get(&sector)
+            .is_some_and(|field| field.grid.revision == grid.revision);
+        let computing = flow_fields
+            .tasks
+            .get(&sector)
+            .is_some_and(|(revision, _)| *revision == grid.revision);
+        if current || computing {
+            continue;
+        }
+        let snapshot = grid.clone();
+        let task = pool.spawn(async move { FlowField::compute(&snapshot, sector) });
+        flow_fields.tasks.insert(sector, (grid.revision, task));
+    }
+}
+
+fn draw_obstacles(mut gizmos: Gizmos, grid: Res<NavGrid>) {
+    for cell in grid.cells() {
+        let color = match grid.cost(cell) {
+            1 => continue,
+            BLOCKED => Color::srgb(0.6, 0.6, 0.6),
+            _ => Color::srgb(0.5, 0.35, 0.2),
+        };
+        let rect = grid.cell_rect(cell);
+        gizmos.rect_2d(rect.center(), 0., rect.size(), color);
+    }
+}
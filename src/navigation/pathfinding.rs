@@ -0,0 +1,184 @@
+use super::{NavGrid, BLOCKED};
+use bevy::prelude::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f32::consts::SQRT_2;
+
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// Open set entry, ordered so the heap pops the lowest estimate first.
+struct Candidate {
+    estimate: f32,
+    cell: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+/// Diagonal distance in cells, a lower bound of the cost since no cell costs less than 1.
+fn octile(a: UVec2, b: UVec2) -> f32 {
+    let dx = a.x.abs_diff(b.x) as f32;
+    let dy = a.y.abs_diff(b.y) as f32;
+    dx.max(dy) + (SQRT_2 - 1.) * dx.min(dy)
+}
+
+/// A* from `start` to `goal`, returning the points to walk through including both ends.
+/// When the goal can't be reached the path leads to the closest reachable cell instead.
+/// None if `start` is outside the grid or blocked.
+pub fn find_path(grid: &NavGrid, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+    let start_cell = grid.cell_at(start).filter(|cell| grid.is_walkable(*cell))?;
+    let goal_cell = grid.clamped_cell_at(goal);
+    let start_index = grid.index(start_cell);
+    let goal_index = grid.index(goal_cell);
+
+    let mut costs = vec![f32::INFINITY; grid.costs.len()];
+    let mut came_from = vec![usize::MAX; grid.costs.len()];
+    let mut open = BinaryHeap::new();
+    costs[start_index] = 0.;
+    open.push(Candidate {
+        estimate: octile(start_cell, goal_cell),
+        cell: start_index,
+    });
+    // Closest to the goal so far, in case it is never reached
+    let mut closest = (octile(start_cell, goal_cell), start_index);
+
+    while let Some(Candidate { estimate, cell }) = open.pop() {
+        if cell == goal_index {
+            closest = (0., cell);
+            break;
+        }
+        let position = grid.cell_of_index(cell);
+        let cost = costs[cell];
+        // Stale entry for a cell reached more cheaply since
+        if estimate > cost + octile(position, goal_cell) + 1e-3 {
+            continue;
+        }
+        let remaining = octile(position, goal_cell);
+        if remaining < closest.0 {
+            closest = (remaining, cell);
+        }
+        for (dx, dy) in NEIGHBOURS {
+            let Some(next) = grid.offset(position, dx, dy) else {
+                continue;
+            };
+            if !grid.is_walkable(next) {
+                continue;
+            }
+            let diagonal = dx != 0 && dy != 0;
+            // No cutting corners past blocked cells
+            if diagonal
+                && !(grid
+                    .offset(position, dx, 0)
+                    .is_some_and(|cell| grid.is_walkable(cell))
+                    && grid
+                        .offset(position, 0, dy)
+                        .is_some_and(|cell| grid.is_walkable(cell)))
+            {
+                continue;
+            }
+            let step = if diagonal { SQRT_2 } else { 1. };
+            let next_index = grid.index(next);
+            let next_cost = cost + step * grid.costs[next_index] as f32;
+            if next_cost < costs[next_index] {
+                costs[next_index] = next_cost;
+                came_from[next_index] = cell;
+                open.push(Candidate {
+                    estimate: next_cost + octile(next, goal_cell),
+                    cell: next_index,
+                });
+            }
+        }
+    }
+
+    let end = closest.1;
+    let mut cells = vec![end];
+    while let Some(&previous) = came_from.get(*cells.last().unwrap()) {
+        if previous == usize::MAX {
+            break;
+        }
+        cells.push(previous);
+    }
+    cells.reverse();
+
+    let mut path = vec![start];
+    path.extend(
+        cells[1..]
+            .iter()
+            .map(|cell| grid.cell_center(grid.cell_of_index(*cell))),
+    );
+    if end == goal_index && grid.cell_at(goal) == Some(goal_cell) {
+        // End on the goal itself rather than the center of its cell
+        if path.len() > 1 {
+            path.pop();
+        }
+        path.push(goal);
+    }
+    Some(path)
+}
+
+/// Drops the points of `path` that can be skipped by walking straight,
+/// without crossing blocked cells or cells costlier than the ones walked around.
+pub fn smooth_path(grid: &NavGrid, path: &[Vec2]) -> Vec<Vec2> {
+    let Some(first) = path.first() else {
+        return Vec::new();
+    };
+    let mut smoothed = vec![*first];
+    let mut anchor = 0;
+    while anchor < path.len() - 1 {
+        let mut next = anchor + 1;
+        let mut max_cost = grid.cost_at(path[anchor]).max(grid.cost_at(path[next]));
+        for candidate in anchor + 2..path.len() {
+            max_cost = max_cost.max(grid.cost_at(path[candidate]));
+            if grid.is_straight_walkable(path[anchor], path[candidate], max_cost) {
+                next = candidate;
+            }
+        }
+        smoothed.push(path[next]);
+        anchor = next;
+    }
+    smoothed
+}
+
+impl NavGrid {
+    fn cost_at(&self, position: Vec2) -> u8 {
+        self.cell_at(position)
+            .map(|cell| self.costs[self.index(cell)])
+            .unwrap_or(BLOCKED)
+    }
+
+    /// Whether walking straight from `from` to `to` stays on cells no costlier than `max_cost`.
+    pub fn is_straight_walkable(&self, from: Vec2, to: Vec2, max_cost: u8) -> bool {
+        // Sampled a few times per cell, which is plenty for unit sized paths
+        let steps = (from.distance(to) / (self.cell_size * 0.25)).ceil().max(1.) as usize;
+        (0..=steps).all(|step| {
+            let cost = self.cost_at(from.lerp(to, step as f32 / steps as f32));
+            cost != BLOCKED && cost <= max_cost
+        })
+    }
+}
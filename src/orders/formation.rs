@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+/// How a group lines up around the target of a move order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Formation {
+    Line,
+    #[default]
+    Box,
+    Wedge,
+    Circle,
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct FormationSettings {
+    pub formation: Formation,
+    /// Distance between neighbouring slots.
+    pub spacing: f32,
+}
+
+impl Default for FormationSettings {
+    fn default() -> Self {
+        FormationSettings {
+            formation: Formation::default(),
+            spacing: 60.,
+        }
+    }
+}
+
+impl Formation {
+    pub fn next(self) -> Self {
+        match self {
+            Formation::Line => Formation::Box,
+            Formation::Box => Formation::Wedge,
+            Formation::Wedge => Formation::Circle,
+            Formation::Circle => Formation::Line,
+        }
+    }
+
+    /// Slot offsets for `count` units centered on the origin, facing `+y` with `+x` to their right.
+    pub fn offsets(self, count: usize, spacing: f32) -> Vec<Vec2> {
+        let offsets: Vec<Vec2> = match self {
+            Formation::Line => (0..count)
+                .map(|index| Vec2::new(index as f32 * spacing, 0.))
+                .collect(),
+            Formation::Box => {
+                let columns = (count as f32).sqrt().ceil().max(1.) as usize;
+                (0..count)
+                    .map(|index| {
+                        let row = index / columns;
+                        // The last row is centered under the full ones
+                        let in_row = (count - row * columns).min(columns);
+                        let column = (index % columns) as f32 + (columns - in_row) as f32 / 2.;
+                        Vec2::new(column * spacing, -(row as f32) * spacing)
+                    })
+                    .collect()
+            }
+            Formation::Wedge => (0..count)
+                .map(|index| {
+                    // The leader at the tip, then one unit to each side per row
+                    let row = index.div_ceil(2) as f32;
+                    let side = if index % 2 == 1 { -1. } else { 1. };
+                    Vec2::new(side * row * spacing, -row * spacing)
+                })
+                .collect(),
+            Formation::Circle => {
+                if count == 1 {
+                    vec![Vec2::ZERO]
+                } else {
+                    // Loose, with a bit more room than the spacing
+                    let radius = (count as f32 * spacing * 1.5 / TAU).max(spacing);
+                    (0..count)
+                        .map(|index| Vec2::from_angle(index as f32 * TAU / count as f32) * radius)
+                        .collect()
+                }
+            }
+        };
+        let center = offsets.iter().sum::<Vec2>() / count.max(1) as f32;
+        offsets.into_iter().map(|offset| offset - center).collect()
+    }
+}
+
+/// Target of every unit at `positions` when the group moves to `target` facing `facing`.
+/// Units keep their place in the group where possible: the left-most unit takes a slot on the left.
+pub fn assign_slots(
+    formation: Formation,
+    positions: &[Vec2],
+    target: Vec2,
+    facing: Vec2,
+    spacing: f32,
+) -> Vec<Vec2> {
+    let forward = facing.try_normalize().unwrap_or(Vec2::Y);
+    let right = -forward.perp();
+    let slots: Vec<Vec2> = formation
+        .offsets(positions.len(), spacing)
+        .into_iter()
+        .map(|offset| right * offset.x + forward * offset.y)
+        .collect();
+
+    // Greedily pair the closest unit and slot, comparing both relative to their centers
+    let center = positions.iter().sum::<Vec2>() / positions.len().max(1) as f32;
+    let mut pairs: Vec<(f32, usize, usize)> = Vec::with_capacity(positions.len() * slots.len());
+    for (unit, position) in positions.iter().enumerate() {
+        for (slot, offset) in slots.iter().enumerate() {
+            pairs.push(((*position - center).distance_squared(*offset), unit, slot));
+        }
+    }
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut targets = vec![None; positions.len()];
+    let mut taken = vec![false; slots.len()];
+    for (_, unit, slot) in pairs {
+        if targets[unit].is_none() && !taken[slot] {
+            targets[unit] = Some(target + slots[slot]);
+            taken[slot] = true;
+        }
+    }
+    targets.into_iter().map(|target| target.unwrap()).collect()
+}
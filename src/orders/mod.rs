@@ -0,0 +1,584 @@
+use crate::actions::input_map::{ActionState, GameAction};
+use crate::actions::{set_cursor_actions, Actions};
+use crate::animation_defintions::AnimationType;
+use crate::navigation::{FlowFields, FollowFlowField, NavGrid, Path};
+use crate::selection::{unit_at, Selected};
+use crate::spatial_index::SpatialIndex;
+use crate::steering::Steering;
+use crate::units::{AttackRange, MoveSpeed, Team, Unit};
+use crate::GameState;
+use bevy::prelude::*;
+use directional_animation::ron_generation::animation_library::MyAnimationClip;
+use directional_animation::ron_generation::facing::FacingTarget;
+use formation::{assign_slots, FormationSettings};
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+
+pub mod formation;
+
+/// Distance at which a unit counts as arrived.
+pub const ARRIVAL_DISTANCE: f32 = 4.;
+/// Right-drag distance in world units before it turns the formation.
+const ORIENT_THRESHOLD: f32 = 8.;
+
+pub struct OrdersPlugin;
+
+/// Walk to the target, then stand idle. Set by the current [`Order`].
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct MoveTo(pub Vec2);
+
+/// The enemy an attack-moving unit stopped to fight.
+/// Gameplay plugins deal the damage, reacting to this being added, changed and removed.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Engaged(pub Entity);
+
+/// What the unit faced before [`Engaged`] turned it toward its enemy.
+/// Only a unit with this marker has its [`FacingTarget`] changed back once the fight ends.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct EngagedFacing(Option<FacingTarget>);
+
+/// Lets [`Order::custom`] downcast custom orders.
+pub trait AsAny {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// An order added by a gameplay plugin, see [`Order::Custom`].
+pub trait CustomOrder: AsAny + fmt::Debug + Send + Sync {
+    /// Where the order takes the unit, for waypoint lines and for placing orders queued after it.
+    fn target(&self) -> Option<Vec2> {
+        None
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Order {
+    Move(Vec2),
+    /// Move, stopping to fight enemies that come within the unit's attack range on the way.
+    /// Fighting only turns the unit toward the enemy and plays the attack animation,
+    /// gameplay plugins deal damage while the unit is [`Engaged`].
+    AttackMove(Vec2),
+    /// Walk back and forth between two points until given another order.
+    Patrol {
+        from: Vec2,
+        to: Vec2,
+    },
+    /// Stand still until given another order.
+    HoldPosition,
+    /// Drop every queued order.
+    Stop,
+    /// Carried out by the systems of the plugin that added it,
+    /// which call [`OrderQueue::complete`] once it is done.
+    Custom(Arc<dyn CustomOrder>),
+}
+
+impl Order {
+    pub fn target(&self) -> Option<Vec2> {
+        match self {
+            Order::Move(target) | Order::AttackMove(target) => Some(*target),
+            Order::Patrol { to, .. } => Some(*to),
+            Order::HoldPosition | Order::Stop => None,
+            Order::Custom(order) => order.target(),
+        }
+    }
+
+    /// The custom order, if it is a `T`.
+    pub fn custom<T: CustomOrder + 'static>(&self) -> Option<&T> {
+        match self {
+            Order::Custom(order) => order.as_ref().as_any().downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Orders of a unit, carried out front to back.
+#[derive(Component, Default, Debug)]
+pub struct OrderQueue {
+    pub orders: VecDeque<Order>,
+    /// The current order has sent the unit off with a [`MoveTo`],
+    /// so losing it means the unit got as close as it could.
+    dispatched: bool,
+}
+
+impl OrderQueue {
+    pub fn current(&self) -> Option<&Order> {
+        self.orders.front()
+    }
+    /// Drops the queued orders for `order`.
+    pub fn replace(&mut self, order: Order) {
+        self.dispatched = false;
+        self.orders.clear();
+        self.orders.push_back(order);
+    }
+    pub fn push(&mut self, order: Order) {
+        self.orders.push_back(order);
+    }
+    /// Moves on to the next order.
+    pub fn complete(&mut self) {
+        self.dispatched = false;
+        self.orders.pop_front();
+    }
+    /// Where the unit ends up after the queued orders, starting from `position`.
+    pub fn last_waypoint(&self, position: Vec2) -> Vec2 {
+        self.orders
+            .iter()
+            .rev()
+            .find_map(Order::target)
+            .unwrap_or(position)
+    }
+}
+
+/// Orders that wait for a target, chosen with a key before right-clicking.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum TargetedOrder {
+    #[default]
+    Move,
+    AttackMove,
+    Patrol,
+}
+
+#[derive(Resource, Default)]
+struct OrderInput {
+    /// World position where the right mouse button went down.
+    drag_start: Option<Vec2>,
+    /// Given by the next right-click.
+    pending: TargetedOrder,
+}
+
+/// This plugin turns right-clicks into orders for the selected units and carries them out
+/// Selected units move in formation around the clicked point, dragging turns the formation
+/// to face the drag direction and F cycles through the formations
+/// T and P make the next right-click an attack-move or patrol, H holds position and X stops
+/// Attack-moving units stop to fight enemies in range and walk on once they are gone
+/// Holding shift queues the order after the current ones instead of replacing them
+/// On touch screens tapping the ground gives the order that a right-click would
+impl Plugin for OrdersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FormationSettings>()
+            .init_resource::<OrderInput>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        cycle_formation,
+                        issue_immediate_orders,
+                        issue_targeted_orders,
+                        draw_formation_preview,
+                        draw_order_queues,
+                    )
+                        .chain()
+                        .after(set_cursor_actions),
+                    execute_orders,
+                    move_units,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn cycle_formation(action_state: Res<ActionState>, mut settings: ResMut<FormationSettings>) {
+    if action_state.just_pressed(GameAction::CycleFormation) {
+        settings.formation = settings.formation.next();
+        info!("Formation: {:?}", settings.formation);
+    }
+}
+
+/// Targets of the selected units for an order given by dragging from `start` to `end`.
+fn formation_targets(
+    settings: &FormationSettings,
+    units: &[(Entity, Vec2)],
+    start: Vec2,
+    end: Vec2,
+) -> Vec<(Entity, Vec2)> {
+    let positions: Vec<Vec2> = units.iter().map(|(_, position)| *position).collect();
+    let center = positions.iter().sum::<Vec2>() / positions.len().max(1) as f32;
+    // Without a drag the group faces the way it walks
+    let facing = if start.distance(end) > ORIENT_THRESHOLD {
+        end - start
+    } else {
+        start - center
+    };
+    let targets = assign_slots(
+        settings.formation,
+        &positions,
+        start,
+        facing,
+        settings.spacing,
+    );
+    units
+        .iter()
+        .zip(targets)
+        .map(|((entity, _), target)| (*entity, target))
+        .collect()
+}
+
+fn issue_immediate_orders(
+    action_state: Res<ActionState>,
+    mut input: ResMut<OrderInput>,
+    mut selected: Query<&mut OrderQueue, (With<Unit>, With<Selected>)>,
+) {
+    if action_state.just_pressed(GameAction::AttackMove) {
+        input.pending = TargetedOrder::AttackMove;
+    }
+    if action_state.just_pressed(GameAction::Patrol) {
+        input.pending = TargetedOrder::Patrol;
+    }
+    if action_state.just_pressed(GameAction::CancelOrder) {
+        input.pending = TargetedOrder::Move;
+    }
+    let order = if action_state.just_pressed(GameAction::HoldPosition) {
+        Order::HoldPosition
+    } else if action_state.just_pressed(GameAction::Stop) {
+        Order::Stop
+    } else {
+        return;
+    };
+    let queue_order = action_state.pressed(GameAction::Append);
+    for mut queue in selected.iter_mut() {
+        if queue_order {
+            queue.push(order.clone());
+        } else {
+            queue.replace(order.clone());
+        }
+    }
+}
+
+fn issue_targeted_orders(
+    action_state: Res<ActionState>,
+    actions: Res<Actions>,
+    settings: Res<FormationSettings>,
+    mut input: ResMut<OrderInput>,
+    mut selected: Query<(Entity, &Transform, &mut OrderQueue), (With<Unit>, With<Selected>)>,
+    units: Query<(Entity, &GlobalTransform, Option<&MyAnimationClip>), With<Unit>>,
+) {
+    // Taps on units select them instead
+    let tap = actions
+        .tap
+        .filter(|tap| unit_at(units.iter(), *tap).is_none());
+    let (start, end) = match tap {
+        Some(tap) => (tap, tap),
+        None => {
+            if action_state.just_pressed(GameAction::Command) {
+                input.drag_start = actions.cursor_position;
+            }
+            if !action_state.just_released(GameAction::Command) {
+                return;
+            }
+            let Some(start) = input.drag_start.take() else {
+                return;
+            };
+            (start, actions.cursor_position.unwrap_or(start))
+        }
+    };
+    let queue_order = action_state.pressed(GameAction::Append);
+    // Queued orders start where the ones before them end
+    let units: Vec<(Entity, Vec2)> = selected
+        .iter()
+        .map(|(entity, transform, queue)| {
+            let position = transform.translation.truncate();
+            let position = if queue_order {
+                queue.last_waypoint(position)
+            } else {
+                position
+            };
+            (entity, position)
+        })
+        .collect();
+    for ((entity, from), (_, target)) in units
+        .iter()
+        .zip(formation_targets(&settings, &units, start, end))
+    {
+        let Ok((_, _, mut queue)) = selected.get_mut(*entity) else {
+            continue;
+        };
+        let order = match input.pending {
+            TargetedOrder::Move => Order::Move(target),
+            TargetedOrder::AttackMove => Order::AttackMove(target),
+            TargetedOrder::Patrol => Order::Patrol {
+                from: *from,
+                to: target,
+            },
+        };
+        if queue_order {
+            queue.push(order);
+        } else {
+            queue.replace(order);
+        }
+    }
+    if !queue_order {
+        input.pending = TargetedOrder::Move;
+    }
+}
+
+fn draw_formation_preview(
+    mut gizmos: Gizmos,
+    action_state: Res<ActionState>,
+    actions: Res<Actions>,
+    settings: Res<FormationSettings>,
+    input: Res<OrderInput>,
+    selected: Query<(Entity, &Transform), (With<Unit>, With<Selected>)>,
+) {
+    let (true, Some(start), Some(end)) = (
+        action_state.pressed(GameAction::Command),
+        input.drag_start,
+        actions.cursor_position,
+    ) else {
+        return;
+    };
+    let units: Vec<(Entity, Vec2)> = selected
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation.truncate()))
+        .collect();
+    let color = Color::srgb(1.0, 0.9, 0.2);
+    for (_, target) in formation_targets(&settings, &units, start, end) {
+        gizmos.circle_2d(target, 6., color);
+    }
+    if start.distance(end) > ORIENT_THRESHOLD {
+        gizmos.arrow_2d(start, end, color);
+    }
+}
+
+fn draw_order_queues(
+    mut gizmos: Gizmos,
+    selected: Query<(&Transform, &OrderQueue), (With<Unit>, With<Selected>)>,
+) {
+    for (transform, queue) in selected.iter() {
+        let mut from = transform.translation.truncate();
+        for order in queue.orders.iter() {
+            let color = match order {
+                Order::AttackMove(_) => Color::srgb(1.0, 0.3, 0.2),
+                Order::Patrol { .. } => Color::srgb(0.3, 0.6, 1.0),
+                _ => Color::srgb(0.2, 1.0, 0.3),
+            };
+            if let Order::Patrol { from: start, to } = order {
+                gizmos.line_2d(*start, *to, color.with_alpha(0.4));
+            }
+            let Some(to) = order.target() else {
+                continue;
+            };
+            gizmos.line_2d(from, to, color);
+            gizmos.circle_2d(to, 4., color);
+            from = to;
+        }
+    }
+}
+
+/// Closest entity within `range` of `position` that `is_enemy`.
+pub fn nearest_enemy(
+    index: &SpatialIndex,
+    position: Vec2,
+    range: f32,
+    is_enemy: impl Fn(Entity) -> bool,
+) -> Option<Entity> {
+    index
+        .in_radius(position, range)
+        .filter(|(other, _)| is_enemy(*other))
+        .min_by(|a, b| {
+            a.1.distance_squared(position)
+                .total_cmp(&b.1.distance_squared(position))
+        })
+        .map(|(entity, _)| entity)
+}
+
+/// Carries out the current order of every unit, fighting enemies that attack-moving units meet.
+pub fn execute_orders(
+    mut commands: Commands,
+    index: Res<SpatialIndex>,
+    teams: Query<&Team>,
+    mut units: Query<(
+        Entity,
+        &Transform,
+        &mut OrderQueue,
+        Option<&MoveTo>,
+        Option<&Engaged>,
+        Option<&EngagedFacing>,
+        Option<&FacingTarget>,
+        Option<(&Team, &AttackRange)>,
+        &mut AnimationType,
+    )>,
+) {
+    for (
+        entity,
+        transform,
+        mut queue,
+        move_to,
+        engaged,
+        engaged_facing,
+        facing_target,
+        fighter,
+        mut animation,
+    ) in units.iter_mut()
+    {
+        let position = transform.translation.truncate();
+        let enemy = match (queue.current(), fighter) {
+            (Some(Order::AttackMove(_)), Some((team, range))) => {
+                nearest_enemy(&index, position, range.0, |other| {
+                    teams.get(other).is_ok_and(|other| other != team)
+                })
+            }
+            _ => None,
+        };
+        if engaged.map(|engaged| engaged.0) != enemy {
+            // Something else may have turned the unit since the fight started
+            let facing_enemy = engaged_facing.is_some()
+                && engaged.map(|engaged| FacingTarget::Entity(engaged.0)) == facing_target.copied();
+            let mut unit = commands.entity(entity);
+            match enemy {
+                Some(enemy) => {
+                    unit.insert(Engaged(enemy));
+                    if engaged.is_none() {
+                        unit.insert((
+                            FacingTarget::Entity(enemy),
+                            EngagedFacing(facing_target.copied()),
+                        ));
+                    } else if facing_enemy {
+                        unit.insert(FacingTarget::Entity(enemy));
+                    }
+                }
+                None => {
+                    unit.remove::<(Engaged, EngagedFacing)>();
+                    if facing_enemy {
+                        match engaged_facing.and_then(|facing| facing.0) {
+                            Some(previous) => unit.insert(previous),
+                            None => unit.remove::<FacingTarget>(),
+                        };
+                    }
+                    if *animation == AnimationType::Attacking {
+                        *animation = AnimationType::Idle;
+                    }
+                }
+            }
+        }
+        // Unreachable targets end the walk wherever the path ends
+        let arrived = |target: Vec2| {
+            position.distance(target) <= ARRIVAL_DISTANCE || (queue.dispatched && move_to.is_none())
+        };
+        let walk_to = match queue.current() {
+            None | Some(Order::Custom(_)) => continue,
+            // Fight where the unit stands, the walk goes on once the enemy is gone
+            Some(Order::AttackMove(_)) if enemy.is_some() => {
+                queue.dispatched = false;
+                None
+            }
+            Some(Order::Move(target) | Order::AttackMove(target)) => {
+                if arrived(*target) {
+                    queue.complete();
+                    continue;
+                }
+                Some(*target)
+            }
+            Some(Order::Patrol { from, to }) => {
+                if arrived(*to) {
+                    let (from, to) = (*to, *from);
+                    queue.orders[0] = Order::Patrol { from, to };
+                    queue.dispatched = false;
+                }
+                queue.current().and_then(Order::target)
+            }
+            Some(Order::HoldPosition) => None,
+            Some(Order::Stop) => {
+                queue.orders.clear();
+                queue.dispatched = false;
+                None
+            }
+        };
+        match walk_to {
+            Some(target) if move_to != Some(&MoveTo(target)) => {
+                commands.entity(entity).insert(MoveTo(target));
+                queue.dispatched = true;
+            }
+            None if move_to.is_some() => {
+                commands.entity(entity).remove::<MoveTo>();
+                if enemy.is_none() {
+                    animation.set_if_neq(AnimationType::Idle);
+                }
+            }
+            _ => {}
+        }
+        if enemy.is_some() {
+            animation.set_if_neq(AnimationType::Attacking);
+        }
+    }
+}
+
+/// Sets the velocity units want toward their next waypoint, [`Steering`] does the moving.
+pub fn move_units(
+    mut commands: Commands,
+    time: Res<Time>,
+    grid: Option<Res<NavGrid>>,
+    flow_fields: Option<Res<FlowFields>>,
+    mut units: Query<(
+        Entity,
+        &Transform,
+        Option<&MoveTo>,
+        Option<&mut Path>,
+        Option<&FollowFlowField>,
+        &MoveSpeed,
+        &mut Steering,
+        &mut AnimationType,
+    )>,
+) {
+    for (entity, transform, move_to, path, flow, speed, mut steering, mut animation) in
+        units.iter_mut()
+    {
+        steering.preferred = Vec2::ZERO;
+        let Some(move_to) = move_to else {
+            continue;
+        };
+        let position = transform.translation.truncate();
+        let field = flow.filter(|flow| flow.goal == move_to.0).map(|flow| {
+            flow_fields
+                .as_ref()
+                .and_then(|fields| fields.get(flow.sector))
+        });
+        let waypoint = match (field, path) {
+            // Wait for the field
+            (Some(None), _) => continue,
+            (Some(Some(field)), _) => {
+                if field.contains(position) {
+                    Some(move_to.0).filter(|target| position.distance(*target) > ARRIVAL_DISTANCE)
+                } else {
+                    field
+                        .direction_at(position)
+                        .map(|direction| position + direction * field.grid.cell_size)
+                }
+            }
+            (None, Some(mut path)) if path.goal == move_to.0 => {
+                while path
+                    .waypoints
+                    .front()
+                    .is_some_and(|waypoint| position.distance(*waypoint) <= ARRIVAL_DISTANCE)
+                {
+                    path.waypoints.pop_front();
+                }
+                path.waypoints.front().copied()
+            }
+            // Wait for the path, or walk straight without a navigation grid
+            _ if grid.is_some() => continue,
+            _ => Some(move_to.0).filter(|target| position.distance(*target) > ARRIVAL_DISTANCE),
+        };
+        let Some(waypoint) = waypoint else {
+            commands
+                .entity(entity)
+                .remove::<(MoveTo, Path, FollowFlowField)>();
+            animation.set_if_neq(AnimationType::Idle);
+            continue;
+        };
+        let offset = waypoint - position;
+        let distance = offset.length();
+        // Slow down to stop on the waypoint rather than overshoot it
+        let speed = speed
+            .0
+            .min(distance / time.delta_seconds().max(f32::EPSILON));
+        steering.preferred = offset / distance * speed;
+        animation.set_if_neq(AnimationType::Running);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::actions::input_map::{ActionState, GameAction};
+use crate::actions::{set_cursor_actions, Actions};
+use crate::animation_defintions::Character;
+use crate::spatial_index::SpatialIndex;
+use crate::units::Unit;
+use crate::GameState;
+use bevy::prelude::*;
+use directional_animation::ron_generation::animation_library::MyAnimationClip;
+
+/// Cursor movement in world units before a click becomes a box selection.
+const DRAG_THRESHOLD: f32 = 4.;
+const DOUBLE_CLICK_SECONDS: f32 = 0.3;
+/// Half size of the box used to pick units that have no clip yet.
+const FALLBACK_HALF_SIZE: f32 = 16.;
+
+pub struct SelectionPlugin;
+
+/// Marks the units the player currently commands.
+#[derive(Component)]
+pub struct Selected;
+
+/// Units stored under the number keys with ctrl+number.
+#[derive(Resource, Default)]
+pub struct ControlGroups {
+    pub groups: [Vec<Entity>; 10],
+}
+
+#[derive(Resource, Default)]
+struct SelectionDrag {
+    /// World position where the left mouse button went down.
+    start: Option<Vec2>,
+    /// Time and unit of the last click, for double-clicks.
+    last_click: Option<(f32, Entity)>,
+}
+
+/// This plugin selects units with the mouse, taps and control groups, and draws selection rings
+/// Click selects a unit, dragging selects everything in the box, shift adds to the selection
+/// and double-click selects every unit of the same character on screen
+/// Tapping a unit works like clicking it, taps on the ground are left for orders
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ControlGroups>()
+            .init_resource::<SelectionDrag>()
+            .add_systems(
+                Update,
+                (select_units, control_groups, draw_selection)
+                    .chain()
+                    .after(set_cursor_actions)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// World space box of a unit, from the clip hitbox if it has one, else from the frame size.
+pub fn unit_bounds(transform: &GlobalTransform, clip: Option<&MyAnimationClip>) -> Rect {
+    let local = match clip {
+        Some(clip) => clip.local_hitbox().unwrap_or_else(|| {
+            let size = clip.display_size();
+            Rect::from_center_size(-clip.anchor().as_vec() * size, size)
+        }),
+        None => Rect::from_center_half_size(Vec2::ZERO, Vec2::splat(FALLBACK_HALF_SIZE)),
+    };
+    Rect::from_corners(
+        transform.transform_point(local.min.extend(0.)).truncate(),
+        transform.transform_point(local.max.extend(0.)).truncate(),
+    )
+}
+
+/// The unit under `point` closest to its center.
+pub fn unit_at<'a>(
+    units: impl Iterator<Item = (Entity, &'a GlobalTransform, Option<&'a MyAnimationClip>)>,
+    point: Vec2,
+) -> Option<Entity> {
+    units
+        .filter(|(_, transform, clip)| unit_bounds(transform, *clip).contains(point))
+        .min_by(|(_, a, _), (_, b, _)| {
+            let a = a.translation().truncate().distance_squared(point);
+            let b = b.translation().truncate().distance_squared(point);
+            a.total_cmp(&b)
+        })
+        .map(|(entity, ..)| entity)
+}
+
+/// The part of the world the camera shows.
+pub fn visible_world_rect(camera: &Camera, camera_transform: &GlobalTransform) -> Option<Rect> {
+    let viewport = camera.logical_viewport_rect()?;
+    Some(Rect::from_corners(
+        camera.viewport_to_world_2d(camera_transform, viewport.min)?,
+        camera.viewport_to_world_2d(camera_transform, viewport.max)?,
+    ))
+}
+
+fn select_units(
+    mut commands: Commands,
+    action_state: Res<ActionState>,
+    time: Res<Time>,
+    actions: Res<Actions>,
+    index: Res<SpatialIndex>,
+    mut drag: ResMut<SelectionDrag>,
+    units: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &Character,
+            Option<&MyAnimationClip>,
+            Has<Selected>,
+        ),
+        With<Unit>,
+    >,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+) {
+    let under = |point: Vec2| {
+        unit_at(
+            units
+                .iter()
+                .map(|(entity, transform, _, clip, _)| (entity, transform, clip)),
+            point,
+        )
+    };
+    let (start, end) = match actions.tap.filter(|tap| under(*tap).is_some()) {
+        Some(tap) => (tap, tap),
+        None => {
+            if action_state.just_pressed(GameAction::Select) {
+                drag.start = actions.cursor_position;
+            }
+            if !action_state.just_released(GameAction::Select) {
+                return;
+            }
+            let (Some(start), Some(end)) = (drag.start.take(), actions.cursor_position) else {
+                return;
+            };
+            (start, end)
+        }
+    };
+    let adding = action_state.pressed(GameAction::Append);
+    if !adding {
+        for (entity, .., selected) in units.iter() {
+            if selected {
+                commands.entity(entity).remove::<Selected>();
+            }
+        }
+    }
+
+    if start.distance(end) > DRAG_THRESHOLD {
+        for (entity, _) in index.in_rect(Rect::from_corners(start, end)) {
+            commands.entity(entity).insert(Selected);
+        }
+        return;
+    }
+
+    let clicked = under(end).and_then(|entity| units.get(entity).ok());
+    let Some((entity, _, character, _, selected)) = clicked else {
+        drag.last_click = None;
+        return;
+    };
+
+    let now = time.elapsed_seconds();
+    let double_click = drag.last_click.is_some_and(|(last_time, last_entity)| {
+        last_entity == entity && now - last_time <= DOUBLE_CLICK_SECONDS
+    });
+    drag.last_click = Some((now, entity));
+    if double_click {
+        let visible = camera
+            .get_single()
+            .ok()
+            .and_then(|(camera, transform)| visible_world_rect(camera, transform));
+        let on_screen: Vec<Entity> = match visible {
+            Some(visible) => index.in_rect(visible).map(|(entity, _)| entity).collect(),
+            None => units.iter().map(|(entity, ..)| entity).collect(),
+        };
+        for other in on_screen {
+            if units
+                .get(other)
+                .is_ok_and(|(_, _, other_character, ..)| other_character == character)
+            {
+                commands.entity(other).insert(Selected);
+            }
+        }
+    } else if adding && selected {
+        commands.entity(entity).remove::<Selected>();
+    } else {
+        commands.entity(entity).insert(Selected);
+    }
+}
+
+fn control_groups(
+    mut commands: Commands,
+    action_state: Res<ActionState>,
+    mut groups: ResMut<ControlGroups>,
+    units: Query<(Entity, Has<Selected>), With<Unit>>,
+) {
+    let assign = action_state.pressed(GameAction::AssignGroup);
+    for index in 0..groups.groups.len() {
+        if !action_state.just_pressed(GameAction::ControlGroup(index as u8)) {
+            continue;
+        }
+        if assign {
+            groups.groups[index] = units
+                .iter()
+                .filter(|(_, selected)| *selected)
+                .map(|(entity, _)| entity)
+                .collect();
+            continue;
+        }
+        // Forget units that died since the group was made
+        groups.groups[index].retain(|entity| units.contains(*entity));
+        if !action_state.pressed(GameAction::Append) {
+            for (entity, selected) in units.iter() {
+                if selected {
+                    commands.entity(entity).remove::<Selected>();
+                }
+            }
+        }
+        for entity in groups.groups[index].iter() {
+            commands.entity(*entity).insert(Selected);
+        }
+    }
+}
+
+fn draw_selection(
+    mut gizmos: Gizmos,
+    action_state: Res<ActionState>,
+    actions: Res<Actions>,
+    drag: Res<SelectionDrag>,
+    selected: Query<(&GlobalTransform, Option<&MyAnimationClip>), (With<Unit>, With<Selected>)>,
+) {
+    let ring_color = Color::srgb(0.2, 1.0, 0.3);
+    for (transform, clip) in selected.iter() {
+        // A flat ring around the feet
+        let bounds = unit_bounds(transform, clip);
+        let radius = bounds.width().max(bounds.height()) * 0.4;
+        gizmos.ellipse_2d(
+            Vec2::new(bounds.center().x, bounds.min.y),
+            0.,
+            Vec2::new(radius, radius * 0.4),
+            ring_color,
+        );
+    }
+
+    if let (true, Some(start), Some(end)) = (
+        action_state.pressed(GameAction::Select),
+        drag.start,
+        actions.cursor_position,
+    ) {
+        if start.distance(end) > DRAG_THRESHOLD {
+            let area = Rect::from_corners(start, end);
+            gizmos.rect_2d(area.center(), 0., area.size(), ring_color);
+        }
+    }
+}
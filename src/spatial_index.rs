@@ -0,0 +1,176 @@
+use crate::units::Unit;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+pub struct SpatialIndexPlugin;
+
+/// Positions of every unit bucketed in square cells, for finding units near a point
+/// without going through all of them. Updated before `Update` from the units that moved.
+#[derive(Resource)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<(Entity, Vec2)>>,
+    positions: HashMap<Entity, Vec2>,
+    /// Cells that ever held an entity, so nearest searches know when to give up.
+    extent: Option<IRect>,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        SpatialIndex::new(128.)
+    }
+}
+
+impl SpatialIndex {
+    pub fn new(cell_size: f32) -> Self {
+        SpatialIndex {
+            cell_size,
+            cells: HashMap::default(),
+            positions: HashMap::default(),
+            extent: None,
+        }
+    }
+
+    fn cell(&self, position: Vec2) -> IVec2 {
+        (position / self.cell_size).floor().as_ivec2()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn position(&self, entity: Entity) -> Option<Vec2> {
+        self.positions.get(&entity).copied()
+    }
+
+    /// Adds `entity` at `position`, or moves it there.
+    pub fn insert(&mut self, entity: Entity, position: Vec2) {
+        let cell = self.cell(position);
+        if let Some(previous) = self.positions.insert(entity, position) {
+            let previous = self.cell(previous);
+            if previous == cell {
+                let entries = self.cells.get_mut(&cell).unwrap();
+                let entry = entries.iter_mut().find(|(other, _)| *other == entity);
+                entry.unwrap().1 = position;
+                return;
+            }
+            self.remove_from_cell(entity, previous);
+        }
+        self.cells.entry(cell).or_default().push((entity, position));
+        let extent = IRect::from_corners(cell, cell);
+        self.extent = Some(self.extent.map_or(extent, |other| other.union(extent)));
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        if let Some(position) = self.positions.remove(&entity) {
+            self.remove_from_cell(entity, self.cell(position));
+        }
+    }
+
+    fn remove_from_cell(&mut self, entity: Entity, cell: IVec2) {
+        let Some(entries) = self.cells.get_mut(&cell) else {
+            return;
+        };
+        entries.retain(|(other, _)| *other != entity);
+        if entries.is_empty() {
+            self.cells.remove(&cell);
+        }
+    }
+
+    fn cells_in(&self, min: IVec2, max: IVec2) -> impl Iterator<Item = &(Entity, Vec2)> + '_ {
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+    }
+
+    /// Entities inside `rect`, in no particular order.
+    pub fn in_rect(&self, rect: Rect) -> impl Iterator<Item = (Entity, Vec2)> + '_ {
+        self.cells_in(self.cell(rect.min), self.cell(rect.max))
+            .filter(move |(_, position)| rect.contains(*position))
+            .copied()
+    }
+
+    /// Entities within `radius` of `center`, in no particular order.
+    pub fn in_radius(
+        &self,
+        center: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = (Entity, Vec2)> + '_ {
+        self.cells_in(self.cell(center - radius), self.cell(center + radius))
+            .filter(move |(_, position)| position.distance_squared(center) <= radius * radius)
+            .copied()
+    }
+
+    /// The `count` entities closest to `point`, closest first.
+    pub fn nearest(&self, point: Vec2, count: usize) -> Vec<(Entity, Vec2)> {
+        let Some(extent) = self.extent.filter(|_| count > 0) else {
+            return Vec::new();
+        };
+        let center = self.cell(point);
+        // Rings of cells further than this from the point's cell are all empty
+        let last_ring = [
+            center.x - extent.min.x,
+            extent.max.x - center.x,
+            center.y - extent.min.y,
+            extent.max.y - center.y,
+        ]
+        .into_iter()
+        .max()
+        .unwrap()
+        .max(0);
+
+        let mut found: Vec<(f32, Entity, Vec2)> = Vec::new();
+        for ring in 0..=last_ring {
+            let ring_cells = (-ring..=ring).flat_map(|y| {
+                (-ring..=ring)
+                    .filter(move |x| x.abs() == ring || y.abs() == ring)
+                    .map(move |x| center + IVec2::new(x, y))
+            });
+            for cell in ring_cells {
+                for (entity, position) in self.cells.get(&cell).into_iter().flatten() {
+                    found.push((position.distance_squared(point), *entity, *position));
+                }
+            }
+            // Anything in the next rings is at least this far away
+            let reach = ring as f32 * self.cell_size;
+            if found.len() >= count {
+                found.sort_by(|a, b| a.0.total_cmp(&b.0));
+                if found[count - 1].0 <= reach * reach {
+                    break;
+                }
+            }
+        }
+        found.sort_by(|a, b| a.0.total_cmp(&b.0));
+        found
+            .into_iter()
+            .take(count)
+            .map(|(_, entity, position)| (entity, position))
+            .collect()
+    }
+}
+
+/// This plugin keeps the [`SpatialIndex`] in step with the units
+impl Plugin for SpatialIndexPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpatialIndex>()
+            .add_systems(PreUpdate, update_spatial_index);
+    }
+}
+
+fn update_spatial_index(
+    mut index: ResMut<SpatialIndex>,
+    moved: Query<(Entity, &Transform), (With<Unit>, Changed<Transform>)>,
+    mut removed: RemovedComponents<Unit>,
+) {
+    for entity in removed.read() {
+        index.remove(entity);
+    }
+    for (entity, transform) in moved.iter() {
+        index.insert(entity, transform.translation.truncate());
+    }
+}
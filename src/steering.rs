@@ -0,0 +1,199 @@
+use crate::navigation::NavGrid;
+use crate::orders::move_units;
+use crate::spatial_index::SpatialIndex;
+use crate::units::{MoveSpeed, Unit};
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use directional_animation::ron_generation::animator::MovementDirection;
+use std::f32::consts::PI;
+
+/// How far ahead units look for others to avoid, in world units.
+const NEIGHBOUR_DISTANCE: f32 = 120.;
+/// Collisions further away than this, in seconds, are ignored.
+const TIME_HORIZON: f32 = 1.;
+/// How much units would rather slow down or turn than risk a collision, in world units per second.
+const AVOIDANCE_WEIGHT: f32 = 75.;
+/// Speed at which overlapping units are pushed apart, per world unit of overlap.
+const SEPARATION_STIFFNESS: f32 = 8.;
+/// Turn in radians before the facing follows the velocity, so pushes don't flicker the sprite.
+const FACING_THRESHOLD: f32 = 0.2;
+
+pub struct SteeringPlugin;
+
+/// Velocity of a unit, steered around the others.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Steering {
+    /// Where the unit wants to go, set by the order it carries out.
+    pub preferred: Vec2,
+    /// Where it actually goes after avoiding and separating from its neighbours.
+    pub velocity: Vec2,
+    pub radius: f32,
+}
+
+impl Default for Steering {
+    fn default() -> Self {
+        Steering {
+            preferred: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+            radius: 20.,
+        }
+    }
+}
+
+/// Seconds until two circles `radius` apart collide, `offset` being the position of the other
+/// relative to the first and `velocity` the velocity of the first relative to the other.
+/// None if they never do or already overlap.
+pub fn time_to_collision(offset: Vec2, velocity: Vec2, radius: f32) -> Option<f32> {
+    let c = offset.length_squared() - radius * radius;
+    let b = offset.dot(velocity);
+    let a = velocity.length_squared();
+    let discriminant = b * b - a * c;
+    if c < 0. || b <= 0. || discriminant < 0. {
+        return None;
+    }
+    Some((b - discriminant.sqrt()) / a)
+}
+
+struct Agent {
+    position: Vec2,
+    steering: Steering,
+}
+
+/// This plugin moves units with the velocity they want, slowing and turning to avoid each other
+/// and pushing apart the ones that overlap, and turns them to face where they go
+impl Plugin for SteeringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (steer_units, apply_velocity)
+                .chain()
+                .after(move_units)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Velocity close to `preferred` least likely to hit a neighbour, sampled in the spirit of RVO
+/// where each of two units takes half the effort of avoiding the other.
+fn avoiding_velocity<'a>(
+    agent: &Agent,
+    neighbours: impl Iterator<Item = &'a Agent> + Clone,
+    max_speed: f32,
+) -> Vec2 {
+    let preferred = agent.steering.preferred;
+    let speed = preferred.length().min(max_speed);
+    if speed == 0. {
+        return Vec2::ZERO;
+    }
+    let heading = preferred / preferred.length();
+    let mut best = (f32::INFINITY, Vec2::ZERO);
+    for angle in [0., 1., -1., 2., -2., 3., -3., 4., -4., 6., -6.] {
+        for scale in [1., 0.5] {
+            let candidate = Vec2::from_angle(angle * PI / 8.).rotate(heading) * speed * scale;
+            let soonest = neighbours
+                .clone()
+                .filter_map(|other| {
+                    // Reciprocal: the other unit is expected to dodge by as much
+                    let relative =
+                        2. * candidate - agent.steering.velocity - other.steering.velocity;
+                    time_to_collision(
+                        other.position - agent.position,
+                        relative,
+                        agent.steering.radius + other.steering.radius,
+                    )
+                })
+                .fold(f32::INFINITY, f32::min);
+            let risk = if soonest < TIME_HORIZON {
+                AVOIDANCE_WEIGHT / soonest.max(0.01)
+            } else {
+                0.
+            };
+            let penalty = candidate.distance(preferred) + risk;
+            if penalty < best.0 {
+                best = (penalty, candidate);
+            }
+        }
+    }
+    best.1
+}
+
+fn steer_units(
+    index: Res<SpatialIndex>,
+    mut units: Query<(Entity, &Transform, &MoveSpeed, &mut Steering), With<Unit>>,
+) {
+    let agents: HashMap<Entity, (Agent, f32)> = units
+        .iter()
+        .map(|(entity, transform, speed, steering)| {
+            let agent = Agent {
+                position: transform.translation.truncate(),
+                steering: *steering,
+            };
+            (entity, (agent, speed.0))
+        })
+        .collect();
+
+    for (entity, (agent, max_speed)) in agents.iter() {
+        let neighbours: Vec<&Agent> = index
+            .in_radius(agent.position, NEIGHBOUR_DISTANCE)
+            .filter(|(other, _)| other != entity)
+            .filter_map(|(other, _)| agents.get(&other).map(|(agent, _)| agent))
+            .collect();
+        let mut velocity = avoiding_velocity(agent, neighbours.iter().copied(), *max_speed);
+
+        for other in neighbours {
+            let offset = agent.position - other.position;
+            let overlap = agent.steering.radius + other.steering.radius - offset.length();
+            if overlap > 0. {
+                // Units on the same spot split in a direction that depends on which one they are
+                let away = offset
+                    .try_normalize()
+                    .unwrap_or_else(|| Vec2::from_angle(entity.index() as f32));
+                velocity += away * overlap * SEPARATION_STIFFNESS;
+            }
+        }
+        if let Ok((.., mut steering)) = units.get_mut(*entity) {
+            steering.velocity = velocity.clamp_length_max(*max_speed);
+        }
+    }
+}
+
+fn apply_velocity(
+    time: Res<Time>,
+    grid: Option<Res<NavGrid>>,
+    mut units: Query<(&mut Transform, &Steering, &mut MovementDirection)>,
+) {
+    let walkable = |position: Vec2| {
+        grid.as_ref().is_none_or(|grid| {
+            grid.cell_at(position)
+                .is_some_and(|cell| grid.is_walkable(cell))
+        })
+    };
+    for (mut transform, steering, mut direction) in units.iter_mut() {
+        if steering.velocity == Vec2::ZERO {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let next = position + steering.velocity * time.delta_seconds();
+        // Slide along walls, units already inside one walk out
+        let next = [
+            next,
+            Vec2::new(next.x, position.y),
+            Vec2::new(position.x, next.y),
+        ]
+        .into_iter()
+        .find(|next| walkable(*next) || !walkable(position))
+        .unwrap_or(position);
+        transform.translation = next.extend(transform.translation.z);
+
+        // Face where the unit goes, but only while it goes somewhere of its own accord
+        if steering.preferred == Vec2::ZERO {
+            continue;
+        }
+        let facing = direction.direction.truncate();
+        let heading = steering.velocity.normalize();
+        if facing == Vec2::ZERO || facing.angle_between(heading).abs() > FACING_THRESHOLD {
+            direction.direction = heading.extend(0.);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::animation_defintions::{AnimationType, Character};
+use crate::orders::OrderQueue;
+use crate::steering::Steering;
+use crate::GameState;
+use bevy::prelude::*;
+use directional_animation::ron_generation::animator::MovementDirection;
+
+pub struct UnitsPlugin;
+
+/// Something the player can select and command.
+#[derive(Component)]
+pub struct Unit;
+
+/// How fast a unit walks, in world units per second.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MoveSpeed(pub f32);
+
+impl Default for MoveSpeed {
+    fn default() -> Self {
+        MoveSpeed(150.)
+    }
+}
+
+/// Side a unit fights for, units of another team are enemies.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Team(pub u8);
+
+/// How close enemies have to be for an attack-moving unit to stop and fight, in world units.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct AttackRange(pub f32);
+
+impl Default for AttackRange {
+    fn default() -> Self {
+        AttackRange(60.)
+    }
+}
+
+/// This plugin spawns the units the player starts with
+impl Plugin for UnitsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_units);
+    }
+}
+
+fn spawn_units(mut commands: Commands) {
+    // A pack of wolves below the player
+    for index in 0..8 {
+        let position = Vec3::new(
+            (index % 4) as f32 * 80. - 120.,
+            -150. - (index / 4) as f32 * 80.,
+            0.,
+        );
+        commands.spawn((
+            Unit,
+            MoveSpeed::default(),
+            Team::default(),
+            AttackRange::default(),
+            OrderQueue::default(),
+            Steering::default(),
+            SpriteBundle {
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            Character::Wolf,
+            AnimationType::Idle,
+            MovementDirection {
+                direction: Vec3::NEG_Y,
+            },
+        ));
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use bevy_game::navigation::flow_field::FlowField;
+use bevy_game::navigation::{NavGrid, BLOCKED};
+
+#[test]
+fn test_flow_field_leads_around_walls() {
+    // A wall at x = 8 open only at the top row
+    let mut grid = NavGrid::new(Vec2::ZERO, 1., UVec2::splat(16));
+    grid.set_area_cost(
+        Rect::from_corners(Vec2::new(8., 0.), Vec2::new(9., 15.)),
+        BLOCKED,
+    );
+    let goal = Vec2::new(13.5, 1.5);
+    let field = FlowField::compute(&grid, FlowField::sector_of(&grid, goal));
+
+    for start in [Vec2::new(1.5, 1.5), Vec2::new(6.5, 12.5)] {
+        let mut position = start;
+        let mut steps = 0;
+        while !field.contains(position) {
+            let direction = field.direction_at(position).unwrap();
+            position += direction * 0.25;
+            assert!(grid.is_straight_walkable(position, position, 1));
+            steps += 1;
+            assert!(steps < 400, "stuck at {:?}", position);
+        }
+    }
+    assert!(field.cost_at(Vec2::new(1.5, 1.5)) > field.cost_at(Vec2::new(10.5, 1.5)));
+
+    // Closing the gap cuts the left half off
+    grid.set_cost(UVec2::new(8, 15), BLOCKED);
+    let field = FlowField::compute(&grid, FlowField::sector_of(&grid, goal));
+    assert_eq!(field.direction_at(Vec2::new(1.5, 1.5)), None);
+    assert!(field.cost_at(Vec2::new(1.5, 1.5)).is_infinite());
+}
+
+#[test]
+fn test_flow_field_steps_along_cheapest_way() {
+    // Rough ground makes straight and diagonal steps compete
+    let mut grid = NavGrid::new(Vec2::ZERO, 1., UVec2::splat(16));
+    grid.set_area_cost(Rect::from_corners(Vec2::new(3., 5.), Vec2::new(6., 13.)), 3);
+    grid.set_area_cost(
+        Rect::from_corners(Vec2::new(8., 4.), Vec2::new(10., 10.)),
+        2,
+    );
+    let field = FlowField::compute(&grid, UVec2::new(3, 0));
+
+    for cell in grid.cells() {
+        let position = grid.cell_center(cell);
+        let Some(direction) = field.direction_at(position) else {
+            continue;
+        };
+        // Each step pays for the cell it leaves, the field only ever takes the cheapest
+        let next = position + direction.round() * grid.cell_size;
+        let step = if direction.x != 0. && direction.y != 0. {
+            std::f32::consts::SQRT_2
+        } else {
+            1.
+        };
+        let through = field.cost_at(next) + step * grid.cost(cell) as f32;
+        assert!(
+            (through - field.cost_at(position)).abs() < 1e-4,
+            "{cell} steps {direction} for {through} instead of {}",
+            field.cost_at(position)
+        );
+    }
+}
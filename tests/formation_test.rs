@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+use bevy_game::orders::formation::{assign_slots, Formation};
+
+#[test]
+fn test_formation_offsets_are_centered_and_spaced() {
+    for formation in [
+        Formation::Line,
+        Formation::Box,
+        Formation::Wedge,
+        Formation::Circle,
+    ] {
+        let offsets = formation.offsets(7, 10.);
+        assert_eq!(offsets.len(), 7);
+        assert!(offsets.iter().sum::<Vec2>().length() < 1e-3);
+        for (index, a) in offsets.iter().enumerate() {
+            for b in offsets[index + 1..].iter() {
+                assert!(
+                    a.distance(*b) >= 10. - 1e-3,
+                    "{:?} slots overlap",
+                    formation
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_slots_keep_relative_positions() {
+    // Two units side by side, ordered to face +x: the left one (seen along +x) is the top one
+    let positions = [Vec2::new(0., 50.), Vec2::new(0., -50.)];
+    let targets = assign_slots(
+        Formation::Line,
+        &positions,
+        Vec2::new(500., 0.),
+        Vec2::X,
+        20.,
+    );
+    assert_eq!(targets[0], Vec2::new(500., 10.));
+    assert_eq!(targets[1], Vec2::new(500., -10.));
+
+    let targets = assign_slots(Formation::Line, &positions, Vec2::ZERO, Vec2::NEG_X, 20.);
+    assert_eq!(targets[0], Vec2::new(0., 10.));
+}
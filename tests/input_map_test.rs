@@ -0,0 +1,46 @@
+use bevy::input::gamepad::GamepadButtonType;
+use bevy::prelude::*;
+use bevy_game::actions::input_map::{GameAction, InputBinding, InputMap};
+
+#[test]
+fn test_input_map_round_trip() {
+    let mut input_map = InputMap::default();
+    assert!(input_map.conflicts().is_empty());
+    input_map.rebind(GameAction::Stop, InputBinding::Key(KeyCode::KeyQ));
+
+    let text = input_map.to_ron().unwrap();
+    assert_eq!(InputMap::from_ron(&text).unwrap(), input_map);
+
+    // Actions missing from older files keep their defaults
+    let loaded = InputMap::from_ron("(bindings: {Up: [Key(KeyI)]})").unwrap();
+    assert_eq!(
+        loaded.bindings(GameAction::Up),
+        &[InputBinding::Key(KeyCode::KeyI)]
+    );
+    assert_eq!(
+        loaded.bindings(GameAction::Down),
+        InputMap::default().bindings(GameAction::Down)
+    );
+}
+
+#[test]
+fn test_rebind_and_conflicts() {
+    let mut input_map = InputMap::default();
+    // Replaces the keys, but not the gamepad buttons
+    input_map.rebind(GameAction::Stop, InputBinding::Key(KeyCode::KeyT));
+    assert_eq!(
+        input_map.bindings(GameAction::Stop),
+        &[
+            InputBinding::Key(KeyCode::KeyT),
+            InputBinding::GamepadButton(GamepadButtonType::East)
+        ]
+    );
+
+    let conflicts = input_map.conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].0, InputBinding::Key(KeyCode::KeyT));
+    assert_eq!(
+        conflicts[0].1,
+        vec![GameAction::AttackMove, GameAction::Stop]
+    );
+}
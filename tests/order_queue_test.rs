@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+use bevy_game::animation_defintions::AnimationType;
+use bevy_game::orders::{execute_orders, nearest_enemy, CustomOrder, Engaged, Order, OrderQueue};
+use bevy_game::spatial_index::SpatialIndex;
+use bevy_game::units::{AttackRange, Team};
+use directional_animation::ron_generation::facing::FacingTarget;
+
+#[derive(Debug)]
+struct Gather(Vec2);
+
+impl CustomOrder for Gather {
+    fn target(&self) -> Option<Vec2> {
+        Some(self.0)
+    }
+}
+
+#[test]
+fn test_order_queue_waypoints() {
+    let mut queue = OrderQueue::default();
+    assert_eq!(queue.last_waypoint(Vec2::ZERO), Vec2::ZERO);
+
+    queue.replace(Order::Move(Vec2::new(10., 0.)));
+    queue.push(Order::Patrol {
+        from: Vec2::new(10., 0.),
+        to: Vec2::new(10., 20.),
+    });
+    queue.push(Order::HoldPosition);
+    // Orders that don't move the unit keep the last waypoint
+    assert_eq!(queue.last_waypoint(Vec2::ZERO), Vec2::new(10., 20.));
+
+    queue.push(Order::Custom(std::sync::Arc::new(Gather(Vec2::new(
+        5., 5.,
+    )))));
+    assert_eq!(queue.last_waypoint(Vec2::ZERO), Vec2::new(5., 5.));
+    let gather = queue.orders.back().unwrap().custom::<Gather>().unwrap();
+    assert_eq!(gather.0, Vec2::new(5., 5.));
+
+    queue.complete();
+    assert_eq!(queue.orders.len(), 3);
+    queue.replace(Order::Stop);
+    assert_eq!(queue.orders.len(), 1);
+    assert_eq!(queue.current().unwrap().target(), None);
+}
+
+#[test]
+fn test_attack_move_finds_nearest_enemy() {
+    let mut index = SpatialIndex::new(32.);
+    let unit = Entity::from_raw(0);
+    let ally = Entity::from_raw(1);
+    let near = Entity::from_raw(2);
+    let far = Entity::from_raw(3);
+    index.insert(unit, Vec2::ZERO);
+    index.insert(ally, Vec2::new(10., 0.));
+    index.insert(near, Vec2::new(0., 40.));
+    index.insert(far, Vec2::new(-50., 0.));
+    let is_enemy = |other: Entity| other == near || other == far;
+
+    assert_eq!(nearest_enemy(&index, Vec2::ZERO, 60., is_enemy), Some(near));
+    // Allies in range don't stop the unit
+    assert_eq!(nearest_enemy(&index, Vec2::ZERO, 20., is_enemy), None);
+    index.remove(near);
+    assert_eq!(nearest_enemy(&index, Vec2::ZERO, 60., is_enemy), Some(far));
+}
+
+#[test]
+fn test_fight_gives_back_facing_and_animation() {
+    let mut app = App::new();
+    app.add_systems(Update, execute_orders);
+    let mut queue = OrderQueue::default();
+    queue.replace(Order::AttackMove(Vec2::new(500., 0.)));
+    let looking_at = FacingTarget::Point(Vec3::new(0., 100., 0.));
+    let unit = app
+        .world_mut()
+        .spawn((
+            Transform::default(),
+            queue,
+            Team(0),
+            AttackRange(50.),
+            AnimationType::Running,
+            looking_at,
+        ))
+        .id();
+    let enemy = app.world_mut().spawn(Team(1)).id();
+    let mut index = SpatialIndex::new(32.);
+    index.insert(unit, Vec2::ZERO);
+    index.insert(enemy, Vec2::new(20., 0.));
+    app.insert_resource(index);
+
+    app.update();
+    let world = app.world();
+    assert_eq!(world.get::<Engaged>(unit), Some(&Engaged(enemy)));
+    assert_eq!(
+        world.get::<FacingTarget>(unit),
+        Some(&FacingTarget::Entity(enemy))
+    );
+    assert!(world.get::<AnimationType>(unit) == Some(&AnimationType::Attacking));
+
+    // The unit looks where it did before the fight
+    app.world_mut().resource_mut::<SpatialIndex>().remove(enemy);
+    app.update();
+    let world = app.world();
+    assert!(world.get::<Engaged>(unit).is_none());
+    assert_eq!(world.get::<FacingTarget>(unit), Some(&looking_at));
+    assert!(world.get::<AnimationType>(unit) == Some(&AnimationType::Idle));
+
+    // A facing set by someone else during the fight is kept, as is an animation other than the attack
+    app.world_mut()
+        .resource_mut::<SpatialIndex>()
+        .insert(enemy, Vec2::new(20., 0.));
+    app.update();
+    let elsewhere = FacingTarget::Point(Vec3::new(-100., 0., 0.));
+    app.world_mut()
+        .entity_mut(unit)
+        .insert((elsewhere, AnimationType::Casting));
+    app.world_mut().resource_mut::<SpatialIndex>().remove(enemy);
+    app.update();
+    let world = app.world();
+    assert!(world.get::<Engaged>(unit).is_none());
+    assert_eq!(world.get::<FacingTarget>(unit), Some(&elsewhere));
+    assert!(world.get::<AnimationType>(unit) == Some(&AnimationType::Casting));
+}
@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use bevy_game::navigation::pathfinding::{find_path, smooth_path};
+use bevy_game::navigation::{NavGrid, BLOCKED};
+
+/// 10x10 cells of size 1 with a wall at x = 5 open only at the top row.
+fn walled_grid() -> NavGrid {
+    let mut grid = NavGrid::new(Vec2::ZERO, 1., UVec2::splat(10));
+    grid.set_area_cost(
+        Rect::from_corners(Vec2::new(5., 0.), Vec2::new(6., 9.)),
+        BLOCKED,
+    );
+    grid
+}
+
+#[test]
+fn test_path_goes_around_walls() {
+    let grid = walled_grid();
+    let start = Vec2::new(1.5, 1.5);
+    let goal = Vec2::new(8.5, 1.5);
+    let path = find_path(&grid, start, goal).unwrap();
+    assert_eq!(path.first(), Some(&start));
+    assert_eq!(path.last(), Some(&goal));
+    assert!(path.iter().any(|point| point.y > 9.));
+    for pair in path.windows(2) {
+        assert!(grid.is_straight_walkable(pair[0], pair[1], 1));
+    }
+
+    let smoothed = smooth_path(&grid, &path);
+    assert!(smoothed.len() < path.len());
+    assert_eq!(smoothed.first(), Some(&start));
+    assert_eq!(smoothed.last(), Some(&goal));
+    for pair in smoothed.windows(2) {
+        assert!(grid.is_straight_walkable(pair[0], pair[1], 1));
+    }
+}
+
+#[test]
+fn test_unreachable_goal_leads_to_closest_cell() {
+    let mut grid = walled_grid();
+    // Close the gap
+    grid.set_cost(UVec2::new(5, 9), BLOCKED);
+    let path = find_path(&grid, Vec2::new(1.5, 1.5), Vec2::new(8.5, 1.5)).unwrap();
+    assert_eq!(path.last(), Some(&Vec2::new(4.5, 1.5)));
+
+    // Starting inside a wall has no path
+    assert!(find_path(&grid, Vec2::new(5.5, 1.5), Vec2::new(1.5, 1.5)).is_none());
+}
@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+use bevy_game::selection::unit_at;
+
+#[test]
+fn test_tap_picks_unit_closest_to_center() {
+    let near = Entity::from_raw(0);
+    let far = Entity::from_raw(1);
+    let near_transform = GlobalTransform::from_translation(Vec3::new(10., 0., 0.));
+    let far_transform = GlobalTransform::from_translation(Vec3::new(-10., 0., 0.));
+    let units = || [(near, &near_transform, None), (far, &far_transform, None)].into_iter();
+
+    // Both boxes cover the point, the nearer center wins
+    assert_eq!(unit_at(units(), Vec2::new(4., 0.)), Some(near));
+    assert_eq!(unit_at(units(), Vec2::new(-4., 0.)), Some(far));
+    // Taps on the ground hit nothing
+    assert_eq!(unit_at(units(), Vec2::new(0., 40.)), None);
+}
@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use bevy_game::spatial_index::SpatialIndex;
+
+fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+    entities.sort();
+    entities
+}
+
+#[test]
+fn test_spatial_index_queries() {
+    let mut index = SpatialIndex::new(100.);
+    let entities: Vec<Entity> = (0..5).map(Entity::from_raw).collect();
+    let positions = [
+        Vec2::new(0., 0.),
+        Vec2::new(30., 40.),
+        Vec2::new(-120., 5.),
+        Vec2::new(400., 400.),
+        Vec2::new(-1000., -1000.),
+    ];
+    for (entity, position) in entities.iter().zip(positions) {
+        index.insert(*entity, position);
+    }
+
+    let found = index.in_radius(Vec2::ZERO, 121.).map(|(entity, _)| entity);
+    assert_eq!(sorted(found.collect()), entities[..3].to_vec());
+    let found = index.in_rect(Rect::new(-10., -10., 450., 450.));
+    assert_eq!(
+        sorted(found.map(|(entity, _)| entity).collect()),
+        vec![entities[0], entities[1], entities[3]]
+    );
+
+    let nearest: Vec<Entity> = index
+        .nearest(Vec2::new(350., 350.), 3)
+        .into_iter()
+        .map(|(entity, _)| entity)
+        .collect();
+    assert_eq!(nearest, vec![entities[3], entities[1], entities[0]]);
+    assert_eq!(index.nearest(Vec2::ZERO, 10).len(), 5);
+
+    // Moving across cells and removing
+    index.insert(entities[4], Vec2::new(10., 10.));
+    assert_eq!(index.nearest(Vec2::new(9., 9.), 1)[0].0, entities[4]);
+    index.remove(entities[4]);
+    assert_eq!(index.position(entities[4]), None);
+    assert_eq!(index.len(), 4);
+    assert_eq!(index.nearest(Vec2::new(9., 9.), 1)[0].0, entities[0]);
+}
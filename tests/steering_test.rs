@@ -0,0 +1,22 @@
+use bevy::prelude::*;
+use bevy_game::steering::time_to_collision;
+
+#[test]
+fn test_time_to_collision() {
+    // Heading straight at a unit 100 away at 50 per second, touching at a distance of 20
+    let time = time_to_collision(Vec2::new(100., 0.), Vec2::new(50., 0.), 20.).unwrap();
+    assert!((time - 1.6).abs() < 1e-4);
+    // Moving away, passing wide, or already overlapping
+    assert_eq!(
+        time_to_collision(Vec2::new(100., 0.), Vec2::new(-50., 0.), 20.),
+        None
+    );
+    assert_eq!(
+        time_to_collision(Vec2::new(100., 0.), Vec2::new(50., 50.), 20.),
+        None
+    );
+    assert_eq!(
+        time_to_collision(Vec2::new(10., 0.), Vec2::new(50., 0.), 20.),
+        None
+    );
+}